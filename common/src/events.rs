@@ -1,6 +1,11 @@
 use near_sdk::env;
-use near_sdk::serde::Serialize;
-use near_sdk::serde_json;
+use near_sdk::serde::{de::DeserializeOwned, Serialize};
+use near_sdk::serde_json::{self, Value};
+
+pub mod snapshot;
+pub mod voting;
+
+const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
 
 /// Helper struct to create Standard NEAR Event JSON.
 /// Arguments:
@@ -23,7 +28,7 @@ impl<T: Serialize> NearEvent<T> {
         let s = serde_json::to_string(&self)
             .ok()
             .unwrap_or_else(|| env::abort());
-        format!("EVENT_JSON:{}", s)
+        format!("{}{}", EVENT_JSON_PREFIX, s)
     }
 
     pub fn emit(self) {
@@ -52,6 +57,38 @@ impl<T: Serialize> EventPayload<T> {
     }
 }
 
+/// Typed events of the contract standard.
+///
+/// Implemented by the enums tagged with `event` and `data`, so the event is
+/// emitted in the same format as [`EventPayload`] and can be parsed back with [`parse_event_log`].
+pub trait StandardEvent: Serialize + DeserializeOwned {
+    /// Name of the standard, e.g. ndc-snapshot
+    const STANDARD: &'static str;
+    /// Version of the standard, e.g. 1.0.0
+    const VERSION: &'static str;
+
+    fn emit(self) {
+        NearEvent {
+            standard: Self::STANDARD,
+            version: Self::VERSION,
+            event: self,
+        }
+        .emit()
+    }
+}
+
+/// Parses the `EVENT_JSON:` log into the typed event.
+///
+/// Returns None if the log is not an event of the standard or the event is unknown.
+/// Events of the previous versions are parsed as long as the payload is compatible.
+pub fn parse_event_log<T: StandardEvent>(log: &str) -> Option<T> {
+    let event: Value = serde_json::from_str(log.strip_prefix(EVENT_JSON_PREFIX)?).ok()?;
+    if event.get("standard")?.as_str()? != T::STANDARD {
+        return None;
+    }
+    serde_json::from_value(event).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
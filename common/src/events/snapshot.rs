@@ -0,0 +1,155 @@
+//! Events of the `ndc-snapshot` standard emitted by the voting snapshot contract
+
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, NearToken, PublicKey};
+
+use super::StandardEvent;
+use crate::snapshot::{ChallengeEvidence, Role, SnapshotMetadata};
+
+/// Phase of the snapshot process
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SnapshotPhase {
+    Initialization,
+    SnapshotChallenge,
+    SnapshotHalted,
+    Registration,
+    RegistrationEnded,
+}
+
+// Fields are ordered alphabetically to keep the format of the events built with `json!`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(
+    crate = "near_sdk::serde",
+    tag = "event",
+    content = "data",
+    rename_all = "snake_case"
+)]
+pub enum SnapshotEvent {
+    PhaseChange {
+        attempt: u32,
+        phase: SnapshotPhase,
+        snapshot: Option<SnapshotMetadata>,
+    },
+    VotersLoaded {
+        count: u32,
+        total_eligible_users: u32,
+    },
+    VoterRegistered {
+        account_id: AccountId,
+        vote_weight: u64,
+    },
+    PublicKeyChanged {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    NomineeRegistered {
+        account_id: AccountId,
+        house: String,
+    },
+    SnapshotChallenged {
+        amount: NearToken,
+        attempt: u32,
+        challenger: AccountId,
+        total_challenged: NearToken,
+    },
+    BondRefunded {
+        account_id: AccountId,
+        amount: NearToken,
+    },
+    ChallengeEvidence {
+        attempt: u32,
+        challenger: AccountId,
+        evidence: ChallengeEvidence,
+    },
+    AdminProposed {
+        admin: AccountId,
+        proposed: AccountId,
+    },
+    AdminChanged {
+        new_admin: AccountId,
+        old_admin: AccountId,
+    },
+    RoleGranted {
+        account_id: AccountId,
+        role: Role,
+    },
+    RoleRevoked {
+        account_id: AccountId,
+        role: Role,
+    },
+    Paused {
+        by: AccountId,
+    },
+    Unpaused {
+        by: AccountId,
+        paused_for_ms: u64,
+    },
+}
+
+impl StandardEvent for SnapshotEvent {
+    const STANDARD: &'static str = "ndc-snapshot";
+    const VERSION: &'static str = "1.1.0";
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils;
+
+    use super::*;
+    use crate::events::parse_event_log;
+
+    fn alice() -> AccountId {
+        "alice.near".parse().unwrap()
+    }
+
+    #[test]
+    fn snapshot_event_is_parsed_back() {
+        let event = SnapshotEvent::SnapshotChallenged {
+            amount: NearToken::from_near(1),
+            attempt: 1,
+            challenger: alice(),
+            total_challenged: NearToken::from_near(5),
+        };
+        event.clone().emit();
+
+        let logs = test_utils::get_logs();
+        assert_eq!(
+            logs,
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"snapshot_challenged","data":{"amount":"1000000000000000000000000","attempt":1,"challenger":"alice.near","total_challenged":"5000000000000000000000000"}}"#
+            ]
+        );
+        assert_eq!(parse_event_log::<SnapshotEvent>(&logs[0]), Some(event));
+    }
+
+    #[test]
+    fn previous_version_is_parsed() {
+        let log = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.0.0","event":"phase_change","data":{"attempt":3,"phase":"RegistrationEnded","snapshot":null}}"#;
+        assert_eq!(
+            parse_event_log(log),
+            Some(SnapshotEvent::PhaseChange {
+                attempt: 3,
+                phase: SnapshotPhase::RegistrationEnded,
+                snapshot: None,
+            })
+        );
+    }
+
+    #[test]
+    fn foreign_logs_are_skipped() {
+        assert_eq!(parse_event_log::<SnapshotEvent>("Transfer 1 NEAR"), None);
+        assert_eq!(
+            parse_event_log::<SnapshotEvent>(
+                r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"paused","data":{"by":"alice.near"}}"#
+            ),
+            None
+        );
+        assert_eq!(
+            parse_event_log::<SnapshotEvent>(
+                r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"unknown","data":{}}"#
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Events of the `ndc-voting` standard emitted by the voting contract
+
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, NearToken, Timestamp};
+
+use super::StandardEvent;
+
+/// Phase of the voting
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum VotingPhase {
+    NotStarted,
+    Voting,
+    Tallying,
+    ResultsPublished,
+    Finalized,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(
+    crate = "near_sdk::serde",
+    tag = "event",
+    content = "data",
+    rename_all = "snake_case"
+)]
+pub enum VotingEvent {
+    PhaseChange {
+        phase: VotingPhase,
+    },
+    /// Votes stored at the indices starting from `start_index`.
+    /// Each hash is sha256 of the bs58 ciphertext string followed by the ephemeral public key,
+    /// so the voter can look the ballot up with `get_vote_index`.
+    VotesSubmitted {
+        count: u64,
+        hashes: Vec<Base58CryptoHash>,
+        start_index: u64,
+    },
+    /// `votes_commitment` binds the results to the hash chain over the stored votes
+    ResultsSubmitted {
+        bond: NearToken,
+        challenge_end: Timestamp,
+        relayer: AccountId,
+        votes_commitment: Base58CryptoHash,
+    },
+    /// `from_tally` is set if the results are taken from the on-chain tally
+    /// as the relayer didn't submit them
+    ResultsFinalized {
+        from_tally: bool,
+        refuted_disputes: u64,
+    },
+    Paused {
+        by: AccountId,
+    },
+    Unpaused {
+        by: AccountId,
+        paused_for_ms: u64,
+    },
+    AdminProposed {
+        admin: AccountId,
+        proposed: AccountId,
+    },
+    AdminChanged {
+        new_admin: AccountId,
+        old_admin: AccountId,
+    },
+}
+
+impl StandardEvent for VotingEvent {
+    const STANDARD: &'static str = "ndc-voting";
+    const VERSION: &'static str = "1.0.0";
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils;
+
+    use super::*;
+    use crate::events::parse_event_log;
+
+    #[test]
+    fn voting_event_is_parsed_back() {
+        let event = VotingEvent::PhaseChange {
+            phase: VotingPhase::Tallying,
+        };
+        event.clone().emit();
+
+        let logs = test_utils::get_logs();
+        assert_eq!(
+            logs,
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"phase_change","data":{"phase":"Tallying"}}"#
+            ]
+        );
+        assert_eq!(parse_event_log::<VotingEvent>(&logs[0]), Some(event));
+    }
+
+    #[test]
+    fn results_events_are_parsed_back() {
+        let events = vec![
+            VotingEvent::VotesSubmitted {
+                count: 1,
+                hashes: vec![[1; 32].into()],
+                start_index: 7,
+            },
+            VotingEvent::ResultsSubmitted {
+                bond: NearToken::from_near(10),
+                challenge_end: 1000,
+                relayer: "relayer.near".parse().unwrap(),
+                votes_commitment: [2; 32].into(),
+            },
+            VotingEvent::ResultsFinalized {
+                from_tally: true,
+                refuted_disputes: 0,
+            },
+        ];
+        for event in events.iter().cloned() {
+            event.emit();
+        }
+
+        let parsed: Vec<VotingEvent> = test_utils::get_logs()
+            .iter()
+            .filter_map(|log| parse_event_log(log))
+            .collect();
+        assert_eq!(parsed, events);
+    }
+}
@@ -0,0 +1,70 @@
+//! Elected bodies shared by the snapshot and the voting contracts.
+//! Nominees register into the houses by name, and the votes are counted per house,
+//! so both contracts should accept the same houses.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::NearSchema;
+
+pub const MAX_HOUSE_NAME_LENGTH: usize = 64;
+
+/// Elected body (e.g. House of Merit) with the number of seats in it
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct House {
+    pub name: String,
+    pub seats: u32,
+}
+
+impl House {
+    /// Validates the list of houses: at least one house, unique non-empty names
+    /// up to `MAX_HOUSE_NAME_LENGTH` bytes and at least one seat in each house
+    pub fn are_valid(houses: &[House]) -> bool {
+        !houses.is_empty()
+            && houses.iter().enumerate().all(|(i, house)| {
+                !house.name.is_empty()
+                    && house.name.len() <= MAX_HOUSE_NAME_LENGTH
+                    && house.seats > 0
+                    && houses[..i].iter().all(|other| other.name != house.name)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn house(name: &str, seats: u32) -> House {
+        House {
+            name: name.to_string(),
+            seats,
+        }
+    }
+
+    #[test]
+    fn houses_are_validated() {
+        assert!(House::are_valid(&[
+            house("House of Merit", 15),
+            house("Council of Advisors", 7),
+        ]));
+
+        assert!(!House::are_valid(&[]));
+        assert!(!House::are_valid(&[house("", 1)]));
+        assert!(!House::are_valid(&[house("House of Merit", 0)]));
+        assert!(!House::are_valid(&[
+            house("House of Merit", 1),
+            house("House of Merit", 2),
+        ]));
+        assert!(House::are_valid(&[house(
+            &"a".repeat(MAX_HOUSE_NAME_LENGTH),
+            1
+        )]));
+        assert!(!House::are_valid(&[house(
+            &"a".repeat(MAX_HOUSE_NAME_LENGTH + 1),
+            1
+        )]));
+    }
+}
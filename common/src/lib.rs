@@ -1,12 +1,32 @@
-use near_sdk::{env, Promise, StorageUsage};
+use near_sdk::{env, AccountId, NearToken, Promise, StorageUsage};
 
 pub mod events;
+pub mod house;
+pub mod snapshot;
+pub mod upgrade;
 
 pub fn finalize_storage_check(
     storage_start: StorageUsage,
     additional_storage: StorageUsage,
 ) -> bool {
-    let user_deposit = env::attached_deposit();
+    finalize_storage_check_with_reserve(
+        storage_start,
+        additional_storage,
+        NearToken::from_yoctonear(0),
+    )
+}
+
+/// Same as [`finalize_storage_check`], but keeps the `reserved` part of the attached deposit
+/// (e.g. a bond) in the contract.
+pub fn finalize_storage_check_with_reserve(
+    storage_start: StorageUsage,
+    additional_storage: StorageUsage,
+    reserved: NearToken,
+) -> bool {
+    let user_deposit = match env::attached_deposit().checked_sub(reserved) {
+        Some(deposit) => deposit,
+        None => return false,
+    };
     let storage_used = env::storage_usage()
         .saturating_sub(storage_start)
         .saturating_add(additional_storage);
@@ -23,3 +43,29 @@ pub fn finalize_storage_check(
         false
     }
 }
+
+/// Returns the attached deposit together with the cost of the storage
+/// released since `storage_start` to the predecessor.
+pub fn refund_released_storage(storage_start: StorageUsage) -> NearToken {
+    let storage_released = storage_start.saturating_sub(env::storage_usage());
+    let refund = env::storage_byte_cost()
+        .saturating_mul(storage_released as u128)
+        .saturating_add(env::attached_deposit());
+
+    if refund.as_yoctonear() > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+    refund
+}
+
+/// Returns the cost of the storage released since `storage_start` to `account_id`,
+/// e.g. to the account that paid for the storage instead of the caller.
+pub fn refund_released_storage_to(storage_start: StorageUsage, account_id: AccountId) -> NearToken {
+    let storage_released = storage_start.saturating_sub(env::storage_usage());
+    let refund = env::storage_byte_cost().saturating_mul(storage_released as u128);
+
+    if refund.as_yoctonear() > 0 {
+        Promise::new(account_id).transfer(refund);
+    }
+    refund
+}
@@ -0,0 +1,118 @@
+//! Snapshot payloads shared by the snapshot contract and its events.
+//! The fields are ordered alphabetically, as the events expose them in this order.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, NearSchema, NearToken};
+
+pub const MAX_LINK_LENGTH: usize = 256;
+pub const MAX_IPFS_CID_LENGTH: usize = 128;
+pub const MAX_REASON_LENGTH: usize = 2048;
+pub const MAX_AFFECTED_ACCOUNTS: usize = 50;
+
+/// Snapshot data of the account
+#[derive(
+    Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, NearSchema, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct UserData {
+    pub active_months: u32,
+    pub stake: NearToken,
+}
+
+impl UserData {
+    pub fn new(active_months: u32, stake: NearToken) -> Self {
+        Self {
+            active_months,
+            stake,
+        }
+    }
+}
+
+/// Provenance of the snapshot data loaded into the contract
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, NearSchema, Debug, PartialEq, Clone,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct SnapshotMetadata {
+    /// Block height the snapshot was taken at
+    pub block_height: u64,
+    /// sha256 of the snapshot JSON file
+    pub data_hash: Base58CryptoHash,
+    /// Version of the vote weight formula the snapshot was prepared for
+    pub formula_version: u32,
+    /// IPFS CID of the published snapshot
+    pub ipfs_cid: String,
+}
+
+impl SnapshotMetadata {
+    pub fn is_valid(&self) -> bool {
+        !self.ipfs_cid.is_empty() && self.ipfs_cid.len() <= MAX_IPFS_CID_LENGTH
+    }
+}
+
+/// Account that is wrong in the snapshot with the data the challenger expects
+#[derive(
+    Serialize, Deserialize, NearSchema, BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct AffectedAccount {
+    pub account_id: AccountId,
+    /// `None` if the account shouldn't be in the snapshot
+    pub expected: Option<UserData>,
+}
+
+/// Evidence of what is wrong with the snapshot
+#[derive(
+    Serialize, Deserialize, NearSchema, BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ChallengeEvidence {
+    pub accounts: Vec<AffectedAccount>,
+    pub proof_link: Option<String>,
+    pub reason: String,
+}
+
+impl ChallengeEvidence {
+    pub fn is_valid(&self) -> bool {
+        !self.reason.is_empty()
+            && self.reason.len() <= MAX_REASON_LENGTH
+            && self.accounts.len() <= MAX_AFFECTED_ACCOUNTS
+            && self
+                .proof_link
+                .as_ref()
+                .map_or(true, |link| link.len() <= MAX_LINK_LENGTH)
+    }
+}
+
+/// Access roles of the snapshot contract
+///
+/// The owner is the admin of the contract and can do everything the other roles can.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Clone,
+    Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum Role {
+    // Transferred with `propose_admin` and `accept_admin`
+    Owner,
+    // Loads and corrects the snapshot data during the initialization
+    SnapshotLoader,
+    // Starts the challenge and restarts the process
+    PhaseOperator,
+    // Pauses the contract in emergencies and unpauses it
+    Guardian,
+}
@@ -0,0 +1,63 @@
+use near_sdk::{env, Gas, NearToken, Promise};
+
+/// Name of the method that migrates the state after the upgrade
+pub const MIGRATE_METHOD: &str = "migrate";
+
+/// Storage key of the version of the state layout. Stored apart from the state,
+/// so `migrate` knows which layout to decode.
+pub const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Returns the version of the stored state layout, if it was stored
+pub fn stored_state_version() -> Option<u32> {
+    env::storage_read(STATE_VERSION_KEY)
+        .and_then(|version| version.try_into().ok())
+        .map(u32::from_le_bytes)
+}
+
+/// Returns the version of the stored state layout.
+/// The states written before the version was stored are the first version.
+pub fn read_state_version() -> u32 {
+    stored_state_version().unwrap_or(1)
+}
+
+/// Stores the version of the state layout. Called on init and after each migration.
+pub fn write_state_version(version: u32) {
+    env::storage_write(STATE_VERSION_KEY, &version.to_le_bytes());
+}
+
+/// Deploys the code passed as the raw input of the call and calls `migrate` on it
+/// with empty JSON arguments.
+///
+/// Both actions are in one receipt, so the old code stays if the migration fails.
+pub fn deploy_and_migrate(migrate_gas: Gas) -> Promise {
+    let code = env::input()
+        .filter(|code| !code.is_empty())
+        .unwrap_or_else(|| env::panic_str("Expected the contract code as input"));
+
+    Promise::new(env::current_account_id())
+        .deploy_contract(code)
+        .function_call(
+            MIGRATE_METHOD.to_string(),
+            b"{}".to_vec(),
+            NearToken::from_yoctonear(0),
+            migrate_gas,
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+
+    #[test]
+    fn state_version_defaults_to_first() {
+        testing_env!(VMContextBuilder::new().build());
+        assert_eq!(stored_state_version(), None);
+        assert_eq!(read_state_version(), 1);
+
+        write_state_version(3);
+        assert_eq!(stored_state_version(), Some(3));
+        assert_eq!(read_state_version(), 3);
+    }
+}
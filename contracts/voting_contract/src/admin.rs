@@ -0,0 +1,87 @@
+use crate::events::{emit_admin_changed, emit_admin_proposed};
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// *Transaction*: Proposes the new admin. The transfer is completed with `accept_admin`.
+    ///
+    /// Requirements:
+    /// - Only admin can propose the new admin
+    pub fn propose_admin(&mut self, account_id: AccountId) {
+        self.assert_admin();
+
+        emit_admin_proposed(&self.admin, &account_id);
+        self.pending_admin = Some(account_id);
+    }
+
+    /// *Transaction*: Accepts the admin role proposed with `propose_admin`
+    ///
+    /// Requirements:
+    /// - Only the proposed account can accept the role
+    pub fn accept_admin(&mut self) {
+        let caller = env::predecessor_account_id();
+        require!(
+            self.pending_admin.as_ref() == Some(&caller),
+            NOT_PENDING_ADMIN
+        );
+
+        emit_admin_changed(&self.admin, &caller);
+        self.admin = caller;
+        self.pending_admin = None;
+    }
+
+    pub(crate) fn assert_admin(&self) {
+        require!(env::predecessor_account_id() == self.admin, ADMIN_ONLY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::testing_env;
+
+    use crate::test_utils::*;
+
+    #[test]
+    fn admin_is_transferred_in_two_steps() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.propose_admin(acc(9));
+
+        // The admin keeps the rights until the new one accepts
+        assert_eq!(contract.get_admin(), admin());
+        assert_eq!(contract.get_pending_admin(), Some(acc(9)));
+
+        context.predecessor_account_id = acc(9);
+        testing_env!(context.clone());
+        contract.accept_admin();
+
+        assert_eq!(contract.get_admin(), acc(9));
+        assert_eq!(contract.get_pending_admin(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only admin can call this method")]
+    fn relayer_cannot_propose_admin() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = relayer();
+        testing_env!(context.clone());
+        contract.propose_admin(relayer());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed admin can accept the role")]
+    fn only_proposed_account_can_accept_admin() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.propose_admin(acc(9));
+
+        context.predecessor_account_id = acc(8);
+        testing_env!(context.clone());
+        contract.accept_admin();
+    }
+}
@@ -1,5 +1,65 @@
+use near_sdk::{BlockHeight, Gas, StorageUsage};
+
 pub const RELAYER_ONLY: &str = "Only relayer can call this method";
+pub const GUARDIAN_ONLY: &str = "Only guardian can call this method";
+pub const ADMIN_ONLY: &str = "Only admin can call this method";
+pub const NOT_PENDING_ADMIN: &str = "Only the proposed admin can accept the role";
+pub const PAUSED: &str = "Contract is paused";
+pub const NOT_PAUSED: &str = "Contract is not paused";
+pub const VOTING_NOT_STARTED: &str = "Voting is not started";
+pub const VOTING_STARTED: &str = "Voting is already started";
+pub const REGISTRATION_NOT_ENDED: &str = "Registration in the snapshot contract is not ended";
+pub const SNAPSHOT_UNAVAILABLE: &str = "Failed to get the status of the snapshot contract";
 pub const VOTING_PHASE_OVER: &str = "Voting phase is over";
 pub const VOTING_PHASE_IN_PROGRESS: &str = "Voting phase is in progress";
 pub const DEPOSIT_NOT_ENOUGH: &str = "Deposit is not enough to cover the storage cost";
 pub const INVALID_VOTE_DATA: &str = "Invalid vote data";
+pub const TOO_MANY_VOTES: &str = "Too many votes in the batch";
+pub const DUPLICATE_VOTE: &str = "Vote is already submitted";
+pub const UNKNOWN_HOUSE: &str = "Unknown house";
+pub const INVALID_HOUSES: &str = "Invalid houses configuration";
+pub const INVALID_ENCRYPTION_KEY: &str = "Invalid encryption public key";
+pub const INVALID_SECRET_KEY: &str = "Secret key doesn't match the encryption public key";
+pub const TALLY_IN_PROGRESS: &str = "Another batch of votes is being processed";
+pub const INVALID_BATCH_START: &str = "Votes should be processed in order";
+pub const ALL_VOTES_PROCESSED: &str = "All votes are processed";
+pub const TALLY_NOT_COMPLETED: &str = "On-chain tally is not completed";
+pub const NO_VOTES_TO_TALLY: &str = "No votes to tally";
+pub const VOTES_COMMITMENT_MISMATCH: &str = "Results should reference the final votes commitment";
+pub const RESULTS_ALREADY_SUBMITTED: &str = "Results are already submitted";
+pub const RESULTS_FINALIZED: &str = "Results are finalized";
+pub const NOT_ON_RESULTS_CHALLENGE: &str = "Allowed only during results challenge";
+pub const RESULTS_CHALLENGE_IN_PROGRESS: &str = "Results challenge is in progress";
+pub const INVALID_VOTE_INDICES: &str = "Invalid vote indices";
+pub const ALREADY_DISPUTED: &str = "Already disputed";
+pub const TOO_MANY_DISPUTES: &str = "Too many disputes";
+pub const NO_STATE: &str = "No state to migrate";
+pub const NO_V1_MIGRATION_CONFIG: &str = "Migration from the first version expects the config";
+pub const RELAYER_SHARED_ROLE: &str = "Admin and guardian should differ from the relayer";
+pub const VOTES_BACKFILL_IN_PROGRESS: &str = "Stored votes are not backfilled yet";
+pub const NO_VOTES_BACKFILL: &str = "No votes to backfill";
+pub const UNKNOWN_STATE_VERSION: &str = "Unknown state version";
+
+pub const MAX_DISPUTES: u64 = 50;
+// Keeps the `votes_submitted` event with the hashes within the log length limit
+pub const MAX_VOTES_PER_BATCH: usize = 100;
+
+// Upper bound of the storage for the counted ballot above the plaintext size
+pub const BALLOT_STORAGE_OVERHEAD: StorageUsage = 100;
+
+// Keeps the backfill of the votes stored by the first release within the gas of a single call
+pub const MAX_BACKFILL_BATCH: u64 = 100;
+// Keeps the ballots of the tally batch within the gas of a single callback
+pub const MAX_TALLY_BATCH: u64 = 20;
+// The batch lock is released after this number of blocks if the callback failed
+pub const TALLY_LOCK_TIMEOUT_BLOCKS: BlockHeight = 100;
+
+pub const SNAPSHOT_VIEW_GAS: Gas = Gas::from_tgas(10);
+pub const ON_VOTES_PROCESSED_GAS: Gas = Gas::from_tgas(10);
+// Signature check and the storage writes of a single ballot in `on_votes_processed`
+pub const COUNT_BALLOT_GAS: Gas = Gas::from_tgas(5);
+pub const ON_SNAPSHOT_STATUS_GAS: Gas = Gas::from_tgas(5);
+// Version of the state layout. Bumped with each release that changes the layout.
+pub const STATE_VERSION: u32 = 2;
+// Gas for the migration of the state after the upgrade
+pub const MIGRATE_GAS: Gas = Gas::from_tgas(50);
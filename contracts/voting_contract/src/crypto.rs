@@ -0,0 +1,165 @@
+use aes_siv::{siv::Aes128Siv, KeyInit};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::SecretKey;
+use near_sdk::{env, CryptoHash, CurveType, PublicKey};
+
+use crate::types::EncryptedVoteStorage;
+
+/// Appends the vote to the hash chain over the stored votes: sha256(commitment || vote hash).
+/// The chain starts from 32 zero bytes.
+pub fn chain_vote(commitment: &CryptoHash, vote: &EncryptedVoteStorage) -> CryptoHash {
+    env::sha256_array(&[commitment.as_slice(), &vote.hash()].concat())
+}
+
+/// Parses the secp256k1 public key of the encryption secret. Returns the compressed key.
+pub fn parse_encryption_key(public_key: &[u8]) -> Option<Vec<u8>> {
+    let public_key = k256::PublicKey::from_sec1_bytes(public_key).ok()?;
+    Some(public_key.to_encoded_point(true).as_bytes().to_vec())
+}
+
+/// Parses the revealed secret key
+pub fn parse_secret_key(secret_key: &[u8]) -> Option<SecretKey> {
+    SecretKey::from_be_bytes(secret_key).ok()
+}
+
+/// Checks that the secret key belongs to the compressed public key
+pub fn is_key_pair(secret_key: &SecretKey, public_key: &[u8]) -> bool {
+    secret_key.public_key().to_encoded_point(true).as_bytes() == public_key
+}
+
+/// Decrypts the vote the same way as the relayer does:
+/// ECDH shared secret (sha256 of the compressed point) is used as AES-128-SIV key
+/// without associated data.
+pub fn decrypt_vote(secret_key: &SecretKey, vote: &EncryptedVoteStorage) -> Option<Vec<u8>> {
+    let public_key = k256::PublicKey::from_sec1_bytes(&vote.pubkey).ok()?;
+    let shared_point = (public_key.to_projective() * *secret_key.to_nonzero_scalar()).to_affine();
+    let shared_secret = env::sha256_array(shared_point.to_encoded_point(true).as_bytes());
+
+    let data = bs58::decode(&vote.vote).into_vec().ok()?;
+    let mut cipher = Aes128Siv::new(&shared_secret.into());
+    cipher.decrypt::<[&[u8]; 0], _>([], &data).ok()
+}
+
+/// Verifies the signature of the voting package with the voter's public key
+///
+/// secp256k1 signatures are verified over the 32 bytes digest as the relayer does.
+pub fn verify_signature(message: &[u8], public_key: &PublicKey, signature: &[u8]) -> bool {
+    let key = &public_key.as_bytes()[1..];
+    match public_key.curve_type() {
+        CurveType::ED25519 => match (signature.try_into(), key.try_into()) {
+            (Ok(signature), Ok(key)) => env::ed25519_verify(signature, message, key),
+            _ => false,
+        },
+        // The compact signature has no recovery id, so both candidates are recovered.
+        // High-s signatures are rejected as malleable.
+        CurveType::SECP256K1 => {
+            message.len() == 32
+                && signature.len() == 64
+                && (0..2).any(|v| {
+                    env::ecrecover(message, signature, v, true)
+                        .map_or(false, |recovered| recovered[..] == key[..])
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::Signer;
+    use k256::elliptic_curve::ff::PrimeField;
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn vote_can_be_decrypted_with_revealed_secret() {
+        let package = ballot(1, vec![(acc(2), 10)]);
+        let vote: Option<EncryptedVoteStorage> = encrypt(&package, 3).into();
+
+        let data = decrypt_vote(&secret_key(), &vote.unwrap()).unwrap();
+        assert_eq!(
+            near_sdk::serde_json::from_slice::<crate::types::VotingPackage>(&data).unwrap(),
+            package
+        );
+    }
+
+    #[test]
+    fn vote_cannot_be_decrypted_with_other_secret() {
+        let vote: Option<EncryptedVoteStorage> = encrypt(&ballot(1, vec![(acc(2), 10)]), 3).into();
+
+        let other = parse_secret_key(&[8; 32]).unwrap();
+        assert!(decrypt_vote(&other, &vote.unwrap()).is_none());
+    }
+
+    #[test]
+    fn signed_data_matches_relayer_format() {
+        let package = ballot(1, vec![(acc(2), 10), (acc(3), 5)]);
+
+        assert_eq!(
+            package.signed_data(),
+            br#"{"accountId":"user-1.near","votes":[{"candidate":"user-2.near","weight":10},{"candidate":"user-3.near","weight":5}]}"#
+        );
+    }
+
+    #[test]
+    fn secret_key_is_checked_against_public_key() {
+        let public_key = bs58::decode(encryption_public_key()).into_vec().unwrap();
+
+        assert!(is_key_pair(&secret_key(), &public_key));
+        assert!(!is_key_pair(
+            &parse_secret_key(&[8; 32]).unwrap(),
+            &public_key
+        ));
+    }
+
+    #[test]
+    fn ed25519_signature_is_verified() {
+        testing_env!(VMContextBuilder::new().build());
+
+        let package = ballot(1, vec![(acc(2), 10)]);
+        let signature = bs58::decode(&package.signature).into_vec().unwrap();
+
+        assert!(verify_signature(
+            &package.signed_data(),
+            &voter_pk(1),
+            &signature
+        ));
+        assert!(!verify_signature(
+            &package.signed_data(),
+            &voter_pk(2),
+            &signature
+        ));
+        assert!(!verify_signature(b"other data", &voter_pk(1), &signature));
+    }
+
+    #[test]
+    fn secp256k1_signature_is_verified_over_digest() {
+        let secp = secp256k1::Secp256k1::new();
+        let secret = secp256k1::SecretKey::from_slice(&[9; 32]).unwrap();
+        let public_key = PublicKey::from_parts(
+            CurveType::SECP256K1,
+            secp256k1::PublicKey::from_secret_key(&secp, &secret).serialize_uncompressed()[1..]
+                .to_vec(),
+        )
+        .unwrap();
+
+        let digest = [5; 32];
+        let signature = secp
+            .sign_ecdsa(&secp256k1::Message::from_digest(digest), &secret)
+            .serialize_compact();
+
+        assert!(verify_signature(&digest, &public_key, &signature));
+        assert!(!verify_signature(&[6; 32], &public_key, &signature));
+        // The same signature with the high s is malleable
+        let s = k256::Scalar::from_repr(*k256::FieldBytes::from_slice(&signature[32..])).unwrap();
+        let mut high_s = signature;
+        high_s[32..].copy_from_slice(&(-s).to_bytes());
+        assert!(!verify_signature(&digest, &public_key, &high_s));
+        // The relayer signs only 32 bytes digests with secp256k1 keys
+        assert!(!verify_signature(b"not a digest", &public_key, &signature));
+
+        let ed25519 = voter_key(1).sign(&digest).to_bytes();
+        assert!(!verify_signature(&digest, &public_key, &ed25519));
+    }
+}
@@ -0,0 +1,120 @@
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::{AccountId, CryptoHash, NearToken, Timestamp};
+
+use common_contracts::events::voting::VotingEvent;
+use common_contracts::events::StandardEvent;
+
+use crate::types::VotingStatus;
+
+pub fn emit_phase_change(phase: VotingStatus) {
+    VotingEvent::PhaseChange {
+        phase: phase.event_info(),
+    }
+    .emit();
+}
+
+pub fn emit_votes_submitted(start_index: u64, hashes: Vec<CryptoHash>) {
+    VotingEvent::VotesSubmitted {
+        count: hashes.len() as u64,
+        hashes: hashes.into_iter().map(Into::into).collect(),
+        start_index,
+    }
+    .emit();
+}
+
+pub fn emit_results_submitted(
+    relayer: &AccountId,
+    bond: NearToken,
+    challenge_end: Timestamp,
+    votes_commitment: Base58CryptoHash,
+) {
+    VotingEvent::ResultsSubmitted {
+        bond,
+        challenge_end,
+        relayer: relayer.clone(),
+        votes_commitment,
+    }
+    .emit();
+}
+
+pub fn emit_results_finalized(from_tally: bool, refuted_disputes: u64) {
+    VotingEvent::ResultsFinalized {
+        from_tally,
+        refuted_disputes,
+    }
+    .emit();
+}
+
+pub fn emit_paused(by: &AccountId) {
+    VotingEvent::Paused { by: by.clone() }.emit();
+}
+
+pub fn emit_unpaused(by: &AccountId, paused_for_ms: Timestamp) {
+    VotingEvent::Unpaused {
+        by: by.clone(),
+        paused_for_ms,
+    }
+    .emit();
+}
+
+pub fn emit_admin_proposed(admin: &AccountId, proposed: &AccountId) {
+    VotingEvent::AdminProposed {
+        admin: admin.clone(),
+        proposed: proposed.clone(),
+    }
+    .emit();
+}
+
+pub fn emit_admin_changed(old_admin: &AccountId, new_admin: &AccountId) {
+    VotingEvent::AdminChanged {
+        new_admin: new_admin.clone(),
+        old_admin: old_admin.clone(),
+    }
+    .emit();
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use near_sdk::test_utils;
+
+    use super::*;
+
+    #[test]
+    fn log_phase_change() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"phase_change","data":{"phase":"ResultsPublished"}}"#;
+        emit_phase_change(VotingStatus::ResultsPublished);
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_votes_submitted() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"votes_submitted","data":{"count":2,"hashes":["11111111111111111111111111111111","4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"],"start_index":5}}"#;
+        emit_votes_submitted(5, vec![[0; 32], [1; 32]]);
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_results() {
+        let relayer: AccountId = "relayer.near".parse().unwrap();
+        let expected = vec![
+            r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"results_submitted","data":{"bond":"10000000000000000000000000","challenge_end":1000,"relayer":"relayer.near","votes_commitment":"11111111111111111111111111111111"}}"#,
+            r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"results_finalized","data":{"from_tally":false,"refuted_disputes":1}}"#,
+        ];
+        emit_results_submitted(&relayer, NearToken::from_near(10), 1000, [0; 32].into());
+        emit_results_finalized(false, 1);
+        assert_eq!(expected, test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_admin_transfer() {
+        let admin: AccountId = "admin.near".parse().unwrap();
+        let proposed: AccountId = "new-admin.near".parse().unwrap();
+        let expected = vec![
+            r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"admin_proposed","data":{"admin":"admin.near","proposed":"new-admin.near"}}"#,
+            r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"admin_changed","data":{"new_admin":"new-admin.near","old_admin":"admin.near"}}"#,
+        ];
+        emit_admin_proposed(&admin, &proposed);
+        emit_admin_changed(&admin, &proposed);
+        assert_eq!(expected, test_utils::get_logs());
+    }
+}
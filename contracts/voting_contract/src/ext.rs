@@ -0,0 +1,23 @@
+use near_sdk::{ext_contract, AccountId, NearToken};
+
+use crate::types::{SnapshotStatus, VoterInformation, VotingPackage};
+
+#[ext_contract(ext_snapshot)]
+pub trait ExtSnapshot {
+    fn get_status(&self) -> SnapshotStatus;
+    fn get_voters_info(&self, voters: Vec<AccountId>) -> Vec<(AccountId, VoterInformation)>;
+    fn get_nominees_info(&self, nominees: Vec<AccountId>) -> Vec<(AccountId, String)>;
+}
+
+#[ext_contract(ext_self)]
+pub trait ExtSelf {
+    fn on_snapshot_status(&mut self);
+    fn on_votes_processed(
+        &mut self,
+        ballots: Vec<VotingPackage>,
+        from: u64,
+        to: u64,
+        caller: AccountId,
+        deposit: NearToken,
+    );
+}
@@ -1,83 +1,485 @@
+use std::collections::{HashMap, HashSet};
+
+use k256::SecretKey;
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{UnorderedMap, Vector};
+use near_sdk::collections::{LookupMap, UnorderedMap, Vector};
 use near_sdk::env::panic_str;
-use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault, Timestamp};
+use near_sdk::{
+    env, near_bindgen, require, AccountId, BlockHeight, CryptoHash, NearToken, PanicOnDefault,
+    Promise, PromiseError, PromiseOrValue, Timestamp,
+};
 
+pub mod admin;
 pub mod consts;
+pub mod crypto;
+pub mod events;
+pub mod ext;
+pub mod migration;
+pub mod pause;
+pub mod results;
 pub mod storage;
 pub mod types;
+pub mod versioned;
 pub mod views;
 
 #[cfg(test)]
 pub mod test_utils;
 
+use common_contracts::upgrade::write_state_version;
 use consts::*;
+use events::{emit_phase_change, emit_votes_submitted};
 use storage::StorageKey;
-use types::{EncryptedVoteStorage, EncryptedVoteView};
+use types::{
+    CountedVote, Dispute, EncryptedVoteStorage, EncryptedVoteView, House, ResultsConfig,
+    SnapshotStatus, VoterInformation, VotingPackage, VotingStatus,
+};
+use versioned::{VersionedResultsConfig, VersionedVotingStatus};
+
+// Weights of the candidates of each house
+type HouseWeights = LookupMap<String, UnorderedMap<AccountId, u64>>;
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct Contract {
     votes: Vector<EncryptedVoteStorage>,
+    // Index of each stored vote by its hash, so the voter can check the inclusion of the ballot
+    vote_indices: LookupMap<CryptoHash, u64>,
+    // Hash chain over the stored votes. The submitted results should reference the final one.
+    votes_commitment: CryptoHash,
+    // Set by the migration from the first release, that didn't index and chain the votes.
+    // Number of the stored votes backfilled so far. Cleared once `backfill_votes` catches up.
+    votes_backfill: Option<u64>,
 
-    candidate_weights: UnorderedMap<AccountId, u64>,
+    houses: Vec<House>,
+    // Results of the each house. Proposed by the relayer until finalized.
+    candidate_weights: HouseWeights,
 
+    // Owner of the contract that upgrades it.
+    // Transferred in two steps with `propose_admin` and `accept_admin`.
+    admin: AccountId,
+    pending_admin: Option<AccountId>,
     relayer: AccountId,
+    // Pauses the contract in emergencies, e.g. if the relayer key is compromised
+    guardian: AccountId,
+    // Set while the contract is paused. The deadlines are shifted by the pause duration on unpause.
+    paused_at: Option<Timestamp>,
+    status: VersionedVotingStatus,
+    voting_duration_in_ms: Timestamp,
+    // Set once the voting is started
     end_time_in_ms: Timestamp,
+
+    results_config: VersionedResultsConfig,
+    // End of the challenge window of the results submitted by the relayer
+    results_challenge_end: Option<Timestamp>,
+    relayer_bond: NearToken,
+    disputes: Vector<Dispute>,
+
+    snapshot_contract: AccountId,
+    // Compressed secp256k1 public key of the secret that encrypts the votes
+    encryption_public_key: Vec<u8>,
+
+    // The results computed by the contract from the revealed secret.
+    // Used to refute the disputes or as the final results if the relayer didn't submit them.
+    // Set once the on-chain tally becomes the final results.
+    onchain_tally: bool,
+    processed_votes: u64,
+    // Block height when the batch was sent to the snapshot contract. Expires after
+    // `TALLY_LOCK_TIMEOUT_BLOCKS`, so a failed callback doesn't block the tally forever.
+    processing_batch: Option<BlockHeight>,
+    // Votes of the last valid ballot of each voter to replace it with the later one
+    counted_ballots: LookupMap<AccountId, Vec<CountedVote>>,
+    tally_weights: HouseWeights,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
-    pub fn new(relayer: AccountId, time: Timestamp) -> Self {
+    pub fn new(
+        admin: AccountId,
+        relayer: AccountId,
+        guardian: AccountId,
+        time: Timestamp,
+        houses: Vec<House>,
+        snapshot_contract: AccountId,
+        encryption_public_key: String,
+        results_config: ResultsConfig,
+    ) -> Self {
+        require!(House::are_valid(&houses), INVALID_HOUSES);
+        let encryption_public_key = Self::decode_encryption_key(&encryption_public_key);
+        let (candidate_weights, tally_weights) = Self::init_house_weights(&houses);
+
+        write_state_version(STATE_VERSION);
+
         Contract {
             votes: Vector::new(StorageKey::Votes),
-            candidate_weights: UnorderedMap::new(StorageKey::CandidatesWeights),
+            vote_indices: LookupMap::new(StorageKey::VoteIndices),
+            votes_commitment: [0; 32],
+            votes_backfill: None,
+            houses,
+            candidate_weights,
+            admin,
+            pending_admin: None,
             relayer,
-            end_time_in_ms: time + env::block_timestamp_ms(),
+            guardian,
+            paused_at: None,
+            status: VotingStatus::NotStarted.into(),
+            voting_duration_in_ms: time,
+            end_time_in_ms: 0,
+            results_config: results_config.into(),
+            results_challenge_end: None,
+            relayer_bond: NearToken::from_yoctonear(0),
+            disputes: Vector::new(StorageKey::Disputes),
+            snapshot_contract,
+            encryption_public_key,
+            onchain_tally: false,
+            processed_votes: 0,
+            processing_batch: None,
+            counted_ballots: LookupMap::new(StorageKey::CountedBallots),
+            tally_weights,
+        }
+    }
+
+    /// *Transaction*: Starts the voting that lasts for the configured duration
+    ///
+    /// The voting starts once the snapshot contract confirms that the registration is ended.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only relayer can start the voting
+    /// - Voting should not be started before
+    pub fn start_voting(&mut self) -> Promise {
+        self.assert_not_paused();
+        self.assert_relayer();
+        require!(*self.status == VotingStatus::NotStarted, VOTING_STARTED);
+
+        ext::ext_snapshot::ext(self.snapshot_contract.clone())
+            .with_static_gas(SNAPSHOT_VIEW_GAS)
+            .with_unused_gas_weight(0)
+            .get_status()
+            .then(
+                ext::ext_self::ext(env::current_account_id())
+                    .with_static_gas(ON_SNAPSHOT_STATUS_GAS)
+                    .on_snapshot_status(),
+            )
+    }
+
+    /// *Callback*: Starts the voting if the registration in the snapshot contract is ended.
+    /// The voting window is counted from this point.
+    ///
+    /// Private function
+    #[private]
+    pub fn on_snapshot_status(
+        &mut self,
+        #[callback_result] status: Result<SnapshotStatus, PromiseError>,
+    ) {
+        require!(*self.status == VotingStatus::NotStarted, VOTING_STARTED);
+        match status {
+            Ok(SnapshotStatus::RegistrationEnded(_)) => {}
+            Ok(_) => panic_str(REGISTRATION_NOT_ENDED),
+            Err(_) => panic_str(SNAPSHOT_UNAVAILABLE),
         }
+
+        self.end_time_in_ms = env::block_timestamp_ms() + self.voting_duration_in_ms;
+        *self.status = VotingStatus::Voting;
+        emit_phase_change(*self.status);
     }
 
+    /// *Transaction*: Tries to move the status to the next phase
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    pub fn try_move_stage(&mut self) {
+        self.assert_not_paused();
+        match *self.status {
+            VotingStatus::Voting if env::block_timestamp_ms() >= self.end_time_in_ms => {
+                *self.status = VotingStatus::Tallying;
+                emit_phase_change(*self.status);
+            }
+            // Other phases are changed by the transactions.
+            // Explicitly write all cases to fail on new status
+            VotingStatus::NotStarted
+            | VotingStatus::Voting
+            | VotingStatus::Tallying
+            | VotingStatus::ResultsPublished
+            | VotingStatus::Finalized => {}
+        }
+    }
+
+    /// *Transaction*: Stores the encrypted votes
+    ///
+    /// Each vote is indexed by its hash (`get_vote_index`) and appended to the votes commitment.
+    /// Emits `votes_submitted` with the index of the first vote and the hashes,
+    /// so the relayer can hand each voter a receipt of the ballot.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only relayer can send the votes during the voting phase
+    /// - The votes stored before the upgrade should be backfilled
+    /// - Up to `MAX_VOTES_PER_BATCH` votes per call
+    /// - The same vote can't be submitted twice
+    /// - Relayer should pay for the storage
     #[payable]
     pub fn send_encrypted_votes(&mut self, votes: Vec<EncryptedVoteView>) {
         let storage_start = env::storage_usage();
-        require!(
-            env::block_timestamp_ms() < self.end_time_in_ms,
-            VOTING_PHASE_OVER
-        );
+        self.try_move_stage();
+        match *self.status {
+            VotingStatus::Voting => {}
+            VotingStatus::NotStarted => panic_str(VOTING_NOT_STARTED),
+            VotingStatus::Tallying | VotingStatus::ResultsPublished | VotingStatus::Finalized => {
+                panic_str(VOTING_PHASE_OVER)
+            }
+        }
         self.assert_relayer();
-        let votes: Option<Vec<_>> = votes.into_iter().map(Into::into).collect();
-
-        if let Some(votes) = votes {
-            self.votes.extend(votes);
-        } else {
-            panic_str(INVALID_VOTE_DATA);
+        require!(self.votes_backfill.is_none(), VOTES_BACKFILL_IN_PROGRESS);
+        require!(votes.len() <= MAX_VOTES_PER_BATCH, TOO_MANY_VOTES);
+        let votes: Option<Vec<EncryptedVoteStorage>> = votes.into_iter().map(Into::into).collect();
+        let votes = votes.unwrap_or_else(|| panic_str(INVALID_VOTE_DATA));
+        let start_index = self.votes.len();
+        let hashes: Vec<CryptoHash> = votes.iter().map(EncryptedVoteStorage::hash).collect();
+        for ((index, hash), vote) in (start_index..).zip(hashes.iter()).zip(votes.iter()) {
+            require!(
+                self.vote_indices.insert(hash, &index).is_none(),
+                DUPLICATE_VOTE
+            );
+            self.votes_commitment = crypto::chain_vote(&self.votes_commitment, vote);
         }
 
+        emit_votes_submitted(start_index, hashes);
+        self.votes.extend(votes);
+
         require!(
             common_contracts::finalize_storage_check(storage_start, 0),
             DEPOSIT_NOT_ENOUGH
         );
     }
 
+    /// Decrypts the batch of votes with the revealed secret key and counts them.
+    ///
+    /// Anybody can call it once the secret is revealed. Batches should go in order
+    /// starting from `get_processed_votes`, up to `MAX_TALLY_BATCH` votes each. Voters and nominees
+    /// are checked with the snapshot contract, and the later valid ballot of the voter replaces
+    /// the earlier one.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Voting phase should be over and the results should not be finalized
+    /// - The previous batch should be counted or its lock should expire
+    /// - The secret key (bs58) should match the encryption public key
+    /// - Caller should pay for the storage of the counted ballots. The rest is refunded.
     #[payable]
-    pub fn sumbit_results(&mut self, results: Vec<(AccountId, u64)>) {
-        let storage_start = env::storage_usage();
-        println!("{}, {}", env::block_timestamp_ms(), self.end_time_in_ms);
+    pub fn process_votes(
+        &mut self,
+        secret_key: String,
+        from: u64,
+        limit: u64,
+    ) -> PromiseOrValue<()> {
+        self.try_move_stage();
+        self.assert_counting();
+        require!(!self.is_processing_batch(), TALLY_IN_PROGRESS);
+        require!(from == self.processed_votes, INVALID_BATCH_START);
+        require!(from < self.votes.len(), ALL_VOTES_PROCESSED);
 
-        require!(
-            env::block_timestamp_ms() > self.end_time_in_ms,
-            VOTING_PHASE_IN_PROGRESS
-        );
-        self.assert_relayer();
+        let secret_key = bs58::decode(secret_key)
+            .into_vec()
+            .ok()
+            .and_then(|key| crypto::parse_secret_key(&key))
+            .filter(|key| crypto::is_key_pair(key, &self.encryption_public_key))
+            .unwrap_or_else(|| panic_str(INVALID_SECRET_KEY));
 
-        self.candidate_weights.extend(results);
+        let limit = std::cmp::min(limit, MAX_TALLY_BATCH);
+        let to = std::cmp::min(from.saturating_add(limit), self.votes.len());
+        let ballots = self.decrypt_votes(&secret_key, from, to);
 
+        let deposit = env::attached_deposit();
+        let storage_estimation: u64 = ballots
+            .iter()
+            .map(|ballot| 2 * ballot.signed_data().len() as u64 + BALLOT_STORAGE_OVERHEAD)
+            .sum();
         require!(
-            common_contracts::finalize_storage_check(storage_start, 0),
+            env::storage_byte_cost().saturating_mul(storage_estimation as u128) <= deposit,
             DEPOSIT_NOT_ENOUGH
         );
+
+        if ballots.is_empty() {
+            self.processed_votes = to;
+            if !deposit.is_zero() {
+                Promise::new(env::predecessor_account_id()).transfer(deposit);
+            }
+            return PromiseOrValue::Value(());
+        }
+
+        let voters: HashSet<_> = ballots.iter().map(|b| b.account_id.clone()).collect();
+        let nominees: HashSet<_> = ballots
+            .iter()
+            .flat_map(|b| b.votes.iter().map(|v| v.candidate.clone()))
+            .collect();
+
+        self.processing_batch = Some(env::block_height());
+        let callback_gas = ON_VOTES_PROCESSED_GAS
+            .saturating_add(COUNT_BALLOT_GAS.saturating_mul(ballots.len() as u64));
+        ext::ext_snapshot::ext(self.snapshot_contract.clone())
+            .with_static_gas(SNAPSHOT_VIEW_GAS)
+            .with_unused_gas_weight(0)
+            .get_voters_info(voters.into_iter().collect())
+            .and(
+                ext::ext_snapshot::ext(self.snapshot_contract.clone())
+                    .with_static_gas(SNAPSHOT_VIEW_GAS)
+                    .with_unused_gas_weight(0)
+                    .get_nominees_info(nominees.into_iter().collect()),
+            )
+            .then(
+                ext::ext_self::ext(env::current_account_id())
+                    .with_static_gas(callback_gas)
+                    .on_votes_processed(ballots, from, to, env::predecessor_account_id(), deposit),
+            )
+            .into()
+    }
+
+    /// *Callback*: Counts the decrypted ballots with the voters and nominees data from the snapshot
+    ///
+    /// The batch is ignored if it's stale: its lock expired and the votes were counted by another batch.
+    ///
+    /// Private function
+    #[private]
+    pub fn on_votes_processed(
+        &mut self,
+        #[callback_result] voters: Result<Vec<(AccountId, VoterInformation)>, PromiseError>,
+        #[callback_result] nominees: Result<Vec<(AccountId, String)>, PromiseError>,
+        ballots: Vec<VotingPackage>,
+        from: u64,
+        to: u64,
+        caller: AccountId,
+        deposit: NearToken,
+    ) {
+        let storage_start = env::storage_usage();
+        let is_current = from == self.processed_votes;
+        if is_current {
+            self.processing_batch = None;
+        }
+
+        // The batch can be retried if the snapshot contract is not available
+        if let (true, Ok(voters), Ok(nominees)) = (is_current, voters, nominees) {
+            let voters: HashMap<_, _> = voters.into_iter().collect();
+            let nominees: HashMap<_, _> = nominees
+                .into_iter()
+                .filter_map(|(nominee, house)| {
+                    let index = self.houses.iter().position(|h| h.name == house)?;
+                    Some((nominee, index))
+                })
+                .collect();
+
+            let mut weights: Vec<_> = self
+                .houses
+                .iter()
+                .map(|house| self.house_tally(&house.name))
+                .collect();
+
+            for ballot in ballots {
+                let votes = match self.validate_ballot(&ballot, &voters, &nominees) {
+                    Some(votes) => votes,
+                    None => continue,
+                };
+
+                if let Some(old_votes) = self.counted_ballots.get(&ballot.account_id) {
+                    for vote in old_votes {
+                        let weights = &mut weights[vote.house as usize];
+                        let weight = weights.get(&vote.candidate).unwrap_or_default();
+                        weights.insert(&vote.candidate, &weight.saturating_sub(vote.weight));
+                    }
+                }
+                for vote in votes.iter() {
+                    let weights = &mut weights[vote.house as usize];
+                    let weight = weights.get(&vote.candidate).unwrap_or_default();
+                    weights.insert(&vote.candidate, &weight.saturating_add(vote.weight));
+                }
+                self.counted_ballots.insert(&ballot.account_id, &votes);
+            }
+
+            for (house, weights) in self.houses.iter().zip(weights) {
+                self.tally_weights.insert(&house.name, &weights);
+            }
+            self.processed_votes = to;
+        }
+
+        let storage_used = env::storage_usage().saturating_sub(storage_start);
+        let refund =
+            deposit.saturating_sub(env::storage_byte_cost().saturating_mul(storage_used as u128));
+        if !refund.is_zero() {
+            Promise::new(caller).transfer(refund);
+        }
+    }
+
+    fn is_processing_batch(&self) -> bool {
+        self.processing_batch.map_or(false, |since| {
+            env::block_height() <= since.saturating_add(TALLY_LOCK_TIMEOUT_BLOCKS)
+        })
+    }
+
+    fn decrypt_votes(&self, secret_key: &SecretKey, from: u64, to: u64) -> Vec<VotingPackage> {
+        (from..to)
+            .filter_map(|i| {
+                let vote = self.votes.get(i)?;
+                let data = crypto::decrypt_vote(secret_key, &vote)?;
+                near_sdk::serde_json::from_slice(&data).ok()
+            })
+            .collect()
+    }
+
+    /// Checks the ballot the same way as the relayer does:
+    /// voter should be registered, the signature should be valid, all candidates should be nominees
+    /// and the votes in each house shouldn't exceed the voter weight.
+    fn validate_ballot(
+        &self,
+        ballot: &VotingPackage,
+        voters: &HashMap<AccountId, VoterInformation>,
+        nominees: &HashMap<AccountId, usize>,
+    ) -> Option<Vec<CountedVote>> {
+        let voter = voters.get(&ballot.account_id)?;
+        let signature = bs58::decode(&ballot.signature).into_vec().ok()?;
+        if !crypto::verify_signature(&ballot.signed_data(), &voter.public_key, &signature) {
+            return None;
+        }
+
+        let mut used = vec![0u64; self.houses.len()];
+        ballot
+            .votes
+            .iter()
+            .map(|vote| {
+                let house = *nominees.get(&vote.candidate)?;
+                used[house] = used[house].checked_add(vote.weight)?;
+                (used[house] <= voter.vote_weight).then(|| CountedVote {
+                    house: house as u32,
+                    candidate: vote.candidate.clone(),
+                    weight: vote.weight,
+                })
+            })
+            .collect()
+    }
+
+    fn house_weights(&self, house: &String) -> UnorderedMap<AccountId, u64> {
+        self.candidate_weights
+            .get(house)
+            .unwrap_or_else(|| panic_str(UNKNOWN_HOUSE))
+    }
+
+    fn house_tally(&self, house: &String) -> UnorderedMap<AccountId, u64> {
+        self.tally_weights
+            .get(house)
+            .unwrap_or_else(|| panic_str(UNKNOWN_HOUSE))
+    }
+
+    /// Checks that the votes can be counted: the voting is over and the results are not final
+    fn assert_counting(&self) {
+        match *self.status {
+            VotingStatus::Tallying | VotingStatus::ResultsPublished => {}
+            VotingStatus::NotStarted | VotingStatus::Voting => panic_str(VOTING_PHASE_IN_PROGRESS),
+            VotingStatus::Finalized => panic_str(RESULTS_FINALIZED),
+        }
+    }
+
+    fn assert_not_paused(&self) {
+        require!(self.paused_at.is_none(), PAUSED);
     }
 
     fn assert_relayer(&self) {
@@ -86,13 +488,48 @@ impl Contract {
             consts::RELAYER_ONLY
         );
     }
+
+    /// Decodes the bs58 compressed secp256k1 public key that encrypts the votes
+    fn decode_encryption_key(encryption_public_key: &str) -> Vec<u8> {
+        bs58::decode(encryption_public_key)
+            .into_vec()
+            .ok()
+            .and_then(|key| crypto::parse_encryption_key(&key))
+            .unwrap_or_else(|| panic_str(INVALID_ENCRYPTION_KEY))
+    }
+
+    /// Creates the empty results and on-chain tally of each house
+    fn init_house_weights(houses: &[House]) -> (HouseWeights, HouseWeights) {
+        let mut candidate_weights = LookupMap::new(StorageKey::CandidatesWeights);
+        let mut tally_weights = LookupMap::new(StorageKey::TallyWeights);
+        for house in houses.iter() {
+            let weights = UnorderedMap::new(StorageKey::HouseCandidatesWeights {
+                house: house.name.clone(),
+            });
+            candidate_weights.insert(&house.name, &weights);
+
+            let weights = UnorderedMap::new(StorageKey::HouseTallyWeights {
+                house: house.name.clone(),
+            });
+            tally_weights.insert(&house.name, &weights);
+        }
+        (candidate_weights, tally_weights)
+    }
 }
 
 #[cfg(test)]
 mod relayer_tests {
-    use near_sdk::{testing_env, NearToken};
+    use common_contracts::events::{parse_event_log, voting::VotingEvent};
+    use near_sdk::{env, test_utils::get_logs, testing_env, NearToken};
+
+    use near_sdk::PromiseError;
 
-    use crate::{test_utils::*, types::EncryptedVoteView};
+    use crate::{
+        consts::MAX_VOTES_PER_BATCH,
+        test_utils::*,
+        types::{EncryptedVoteView, SnapshotStatus, VotingStatus},
+        Contract,
+    };
 
     #[test]
     fn can_init_contract() {
@@ -100,6 +537,90 @@ mod relayer_tests {
         testing_env!(context.clone());
         assert_eq!(contract.get_relayer(), relayer());
         assert_eq!(contract.get_end_time(), end_time());
+        assert_eq!(contract.get_status(), VotingStatus::Voting);
+    }
+
+    #[test]
+    #[should_panic(expected = "Voting is not started")]
+    fn cant_send_votes_before_start() {
+        let (mut context, mut contract) = init_ctr();
+        assert_eq!(contract.get_status(), VotingStatus::NotStarted);
+
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+
+        contract.send_encrypted_votes(vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Voting is already started")]
+    fn cant_start_voting_twice() {
+        let (mut context, mut contract) = setup_ctr();
+        context.predecessor_account_id = relayer();
+        testing_env!(context.clone());
+
+        contract.start_voting();
+    }
+
+    #[test]
+    fn voting_starts_once_registration_is_ended() {
+        let (mut context, mut contract) = init_ctr();
+        context.block_timestamp = 100 * MSECOND;
+
+        start_voting(
+            &mut context,
+            &mut contract,
+            Ok(SnapshotStatus::RegistrationEnded(1)),
+        );
+
+        assert_eq!(contract.get_status(), VotingStatus::Voting);
+        assert_eq!(contract.get_end_time(), 100 + end_time());
+    }
+
+    #[test]
+    #[should_panic(expected = "Registration in the snapshot contract is not ended")]
+    fn cant_start_voting_during_registration() {
+        let (mut context, mut contract) = init_ctr();
+
+        start_voting(
+            &mut context,
+            &mut contract,
+            Ok(SnapshotStatus::Registration(0)),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to get the status of the snapshot contract")]
+    fn cant_start_voting_without_snapshot_status() {
+        let (mut context, mut contract) = init_ctr();
+
+        start_voting(&mut context, &mut contract, Err(PromiseError::Failed));
+    }
+
+    #[test]
+    fn status_moves_through_phases() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.block_timestamp = end_time() * MSECOND;
+        testing_env!(context.clone());
+        contract.try_move_stage();
+        assert_eq!(contract.get_status(), VotingStatus::Tallying);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"phase_change","data":{"phase":"Tallying"}}"#
+            ]
+        );
+
+        submit_results(&mut context, &mut contract, vec![(acc(1), 1)]);
+        assert_eq!(contract.get_status(), VotingStatus::ResultsPublished);
+
+        context.block_timestamp = (contract.get_results_challenge_end().unwrap() + 1) * MSECOND;
+        context.attached_deposit = NearToken::from_yoctonear(0);
+        testing_env!(context.clone());
+        contract.finalize_results();
+        assert_eq!(contract.get_status(), VotingStatus::Finalized);
     }
 
     #[test]
@@ -123,21 +644,112 @@ mod relayer_tests {
         contract.send_encrypted_votes(votes.clone());
 
         assert_eq!(contract.get_votes(0, 10), votes);
+        let hashes = vec![
+            env::sha256_array(&[b"vote1".as_slice(), &[1; 65]].concat()).into(),
+            env::sha256_array(&[b"vote2".as_slice(), &[2; 65]].concat()).into(),
+        ];
+        assert_eq!(
+            get_logs()
+                .iter()
+                .filter_map(|log| parse_event_log::<VotingEvent>(log))
+                .collect::<Vec<_>>(),
+            vec![VotingEvent::VotesSubmitted {
+                count: 2,
+                hashes,
+                start_index: 0,
+            }]
+        );
     }
 
     #[test]
-    fn can_submit_results() {
+    #[should_panic(expected = "Too many votes in the batch")]
+    fn cant_send_too_many_votes() {
         let (mut context, mut contract) = setup_ctr();
         context.predecessor_account_id = relayer();
         context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+
+        let vote = encrypt(&ballot(1, vec![(acc(2), 10)]), 100);
+        contract.send_encrypted_votes(vec![vote; MAX_VOTES_PER_BATCH + 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vote is already submitted")]
+    fn cant_send_same_vote_twice() {
+        let (mut context, mut contract) = setup_ctr();
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+
+        let vote = encrypt(&ballot(1, vec![(acc(2), 10)]), 100);
+        contract.send_encrypted_votes(vec![vote.clone()]);
+        contract.send_encrypted_votes(vec![vote]);
+    }
+
+    #[test]
+    fn can_submit_results() {
+        let (mut context, mut contract) = setup_ctr();
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(11);
         context.block_timestamp = (end_time() + 1) * MSECOND;
         testing_env!(context.clone());
 
         let results: Vec<_> = vec![(acc(1), 1), (acc(2), 2), (acc(3), 3)];
 
-        contract.sumbit_results(results.clone());
+        contract.sumbit_results(
+            vec![(house(), results.clone())],
+            contract.get_votes_commitment(),
+        );
+
+        assert_eq!(contract.get_candidate_weights(house(), 0, 10), results);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid houses configuration")]
+    fn cant_init_contract_with_duplicated_houses() {
+        let mut houses = houses();
+        houses.push(houses[0].clone());
+
+        Contract::new(
+            admin(),
+            relayer(),
+            guardian(),
+            end_time(),
+            houses,
+            snapshot(),
+            encryption_public_key(),
+            results_config(),
+        );
+    }
 
-        assert_eq!(contract.get_candidate_weights(0, 10), results);
+    #[test]
+    #[should_panic(expected = "Invalid encryption public key")]
+    fn cant_init_contract_with_invalid_encryption_key() {
+        Contract::new(
+            admin(),
+            relayer(),
+            guardian(),
+            end_time(),
+            houses(),
+            snapshot(),
+            bs58::encode([1; 33]).into_string(),
+            results_config(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown house")]
+    fn cant_submit_results_for_unknown_house() {
+        let (mut context, mut contract) = setup_ctr();
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(11);
+        context.block_timestamp = (end_time() + 1) * MSECOND;
+        testing_env!(context.clone());
+
+        contract.sumbit_results(
+            vec![("House of Lords".to_string(), vec![(acc(1), 1)])],
+            contract.get_votes_commitment(),
+        );
     }
 
     #[test]
@@ -167,13 +779,16 @@ mod relayer_tests {
     fn anybody_cant_submit_results() {
         let (mut context, mut contract) = setup_ctr();
         context.predecessor_account_id = acc(1);
-        context.attached_deposit = NearToken::from_near(1);
+        context.attached_deposit = NearToken::from_near(11);
         context.block_timestamp = (end_time() + 1) * MSECOND;
         testing_env!(context.clone());
 
         let results: Vec<_> = vec![(acc(1), 1), (acc(2), 2), (acc(3), 3)];
 
-        contract.sumbit_results(results.clone());
+        contract.sumbit_results(
+            vec![(house(), results.clone())],
+            contract.get_votes_commitment(),
+        );
     }
 
     #[test]
@@ -204,12 +819,15 @@ mod relayer_tests {
     fn cant_submit_results_before_voting_phase() {
         let (mut context, mut contract) = setup_ctr();
         context.predecessor_account_id = relayer();
-        context.attached_deposit = NearToken::from_near(1);
+        context.attached_deposit = NearToken::from_near(11);
         testing_env!(context.clone());
 
         let results: Vec<_> = vec![(acc(1), 1), (acc(2), 2), (acc(3), 3)];
 
-        contract.sumbit_results(results.clone());
+        contract.sumbit_results(
+            vec![(house(), results.clone())],
+            contract.get_votes_commitment(),
+        );
     }
 
     #[test]
@@ -260,3 +878,268 @@ mod relayer_tests {
         contract.send_encrypted_votes(votes.clone());
     }
 }
+
+#[cfg(test)]
+mod tally_tests {
+    use near_sdk::{
+        mock::MockAction,
+        test_utils::{get_created_receipts, get_logs},
+        testing_env, NearToken, PromiseError,
+    };
+
+    use crate::consts::{MAX_TALLY_BATCH, TALLY_LOCK_TIMEOUT_BLOCKS};
+    use crate::test_utils::*;
+
+    #[test]
+    fn votes_are_counted_on_chain() {
+        let (mut context, mut contract) = setup_ctr();
+        let ballots = vec![
+            ballot(1, vec![(acc(10), 6), (acc(11), 4), (acc(12), 10)]),
+            ballot(2, vec![(acc(11), 10)]),
+            ballot(3, vec![(acc(10), 1)]),
+        ];
+        send_votes(&mut context, &mut contract, &ballots);
+
+        process_batch(&mut context, &mut contract, 0, 2, Ok(voters_info()));
+        assert_eq!(contract.get_processed_votes(), 2);
+        process_batch(&mut context, &mut contract, 2, 2, Ok(voters_info()));
+        assert_eq!(contract.get_processed_votes(), 3);
+        assert!(contract.is_tally_completed());
+        assert!(!contract.is_onchain_tally());
+
+        // Without the relayer results the on-chain tally becomes final
+        contract.finalize_results();
+
+        assert!(get_logs().contains(
+            &r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"results_finalized","data":{"from_tally":true,"refuted_disputes":0}}"#
+                .to_string()
+        ));
+        assert!(contract.is_onchain_tally());
+        assert!(contract.is_results_finalized());
+        assert_eq!(
+            contract.get_winners(house()),
+            vec![(acc(11), 14), (acc(10), 7)]
+        );
+        assert_eq!(
+            contract.get_winners(houses()[1].name.clone()),
+            vec![(acc(12), 10)]
+        );
+    }
+
+    #[test]
+    fn later_ballot_replaces_earlier_one() {
+        let (mut context, mut contract) = setup_ctr();
+        let ballots = vec![
+            ballot(1, vec![(acc(10), 10)]),
+            ballot(1, vec![(acc(11), 3)]),
+            // Invalid ballot doesn't replace the valid one
+            ballot(1, vec![(acc(10), 11)]),
+        ];
+        send_votes(&mut context, &mut contract, &ballots);
+
+        process_batch(&mut context, &mut contract, 0, 1, Ok(voters_info()));
+        process_batch(&mut context, &mut contract, 1, 2, Ok(voters_info()));
+
+        assert_eq!(
+            contract.get_tally_weights(house(), 0, 10),
+            vec![(acc(10), 0), (acc(11), 3)]
+        );
+    }
+
+    #[test]
+    fn invalid_ballots_are_discarded() {
+        let (mut context, mut contract) = setup_ctr();
+
+        let mut forged = ballot(2, vec![(acc(10), 5)]);
+        forged.account_id = acc(3);
+
+        let ballots = vec![
+            // Exceeds the weight in the house
+            ballot(1, vec![(acc(10), 6), (acc(11), 5)]),
+            // Not a nominee
+            ballot(2, vec![(acc(10), 1), (acc(13), 1)]),
+            // Signed by another voter
+            forged,
+            // Not a voter
+            ballot(4, vec![(acc(10), 1)]),
+        ];
+        send_votes(&mut context, &mut contract, &ballots);
+
+        process_batch(&mut context, &mut contract, 0, 10, Ok(voters_info()));
+
+        assert_eq!(contract.get_processed_votes(), 4);
+        assert!(contract.get_tally_weights(house(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn batch_is_retried_if_snapshot_call_fails() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        process_batch(
+            &mut context,
+            &mut contract,
+            0,
+            10,
+            Err(PromiseError::Failed),
+        );
+        assert_eq!(contract.get_processed_votes(), 0);
+        assert!(contract.processing_batch.is_none());
+
+        process_batch(&mut context, &mut contract, 0, 10, Ok(voters_info()));
+        assert_eq!(
+            contract.get_tally_weights(house(), 0, 10),
+            vec![(acc(10), 10)]
+        );
+    }
+
+    #[test]
+    fn failed_callback_does_not_block_tally() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        // The callback of the first attempt fails, e.g. runs out of gas
+        contract.process_votes(secret(), 0, 10);
+        assert!(!contract.is_tally_completed());
+
+        context.block_index += TALLY_LOCK_TIMEOUT_BLOCKS + 1;
+        process_batch(&mut context, &mut contract, 0, 10, Ok(voters_info()));
+        assert!(contract.is_tally_completed());
+        assert_eq!(
+            contract.get_tally_weights(house(), 0, 10),
+            vec![(acc(10), 10)]
+        );
+
+        // The late callback of the expired batch is ignored
+        let ballots = vec![ballot(1, vec![(acc(10), 5)])];
+        contract.on_votes_processed(
+            Ok(voters_info()),
+            Ok(nominees_info()),
+            ballots,
+            0,
+            1,
+            acc(0),
+            NearToken::from_yoctonear(0),
+        );
+        assert_eq!(
+            contract.get_tally_weights(house(), 0, 10),
+            vec![(acc(10), 10)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Another batch of votes is being processed")]
+    fn batch_lock_holds_until_timeout() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        contract.process_votes(secret(), 0, 10);
+        context.block_index += TALLY_LOCK_TIMEOUT_BLOCKS;
+        testing_env!(context.clone());
+        contract.process_votes(secret(), 0, 10);
+    }
+
+    #[test]
+    fn batch_size_is_capped() {
+        let (mut context, mut contract) = setup_ctr();
+        let ballots: Vec<_> = (0..MAX_TALLY_BATCH as u8 + 5)
+            .map(|i| ballot(i, vec![(acc(10), 1)]))
+            .collect();
+        send_votes(&mut context, &mut contract, &ballots);
+
+        process_batch(&mut context, &mut contract, 0, 100, Ok(voters_info()));
+        assert_eq!(contract.get_processed_votes(), MAX_TALLY_BATCH);
+    }
+
+    #[test]
+    fn unused_deposit_is_refunded() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        context.attached_deposit = NearToken::from_near(1);
+        process_batch(&mut context, &mut contract, 0, 10, Ok(voters_info()));
+
+        let receipts = get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == acc(0)
+            && receipt.actions.iter().any(|action| matches!(
+                action,
+                MockAction::Transfer { deposit, .. }
+                    if *deposit > NearToken::from_millinear(990)
+                        && *deposit < NearToken::from_near(1)
+            ))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Secret key doesn't match the encryption public key")]
+    fn cant_process_votes_with_wrong_secret() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        contract.process_votes(bs58::encode([8; 32]).into_string(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Voting phase is in progress")]
+    fn cant_process_votes_during_voting() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        context.block_timestamp = (end_time() - 1) * MSECOND;
+        testing_env!(context.clone());
+        contract.process_votes(secret(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Another batch of votes is being processed")]
+    fn cant_process_batches_concurrently() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        contract.process_votes(secret(), 0, 10);
+        contract.process_votes(secret(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Votes should be processed in order")]
+    fn cant_skip_votes() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[
+                ballot(1, vec![(acc(10), 10)]),
+                ballot(2, vec![(acc(10), 10)]),
+            ],
+        );
+
+        contract.process_votes(secret(), 1, 10);
+    }
+}
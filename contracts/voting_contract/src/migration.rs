@@ -0,0 +1,476 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap, Vector};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, NearSchema, NearToken, Promise, Timestamp};
+
+use common_contracts::upgrade::{deploy_and_migrate, read_state_version, write_state_version};
+
+use crate::consts::{
+    INVALID_HOUSES, MAX_BACKFILL_BATCH, MIGRATE_GAS, NO_STATE, NO_V1_MIGRATION_CONFIG,
+    NO_VOTES_BACKFILL, RELAYER_SHARED_ROLE, STATE_VERSION, UNKNOWN_STATE_VERSION,
+};
+use crate::storage::StorageKey;
+use crate::types::{EncryptedVoteStorage, House, ResultsConfig, VotingStatus};
+use crate::{Contract, ContractExt};
+
+/// State of the first release, that didn't store the state version. Layout version 1.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ContractV1 {
+    pub votes: Vector<EncryptedVoteStorage>,
+    pub candidate_weights: UnorderedMap<AccountId, u64>,
+    pub relayer: AccountId,
+    pub end_time_in_ms: Timestamp,
+}
+
+/// Config of the contract that the first release didn't store. Passed to `migrate` from it.
+#[derive(Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct V1MigrationConfig {
+    pub admin: AccountId,
+    pub guardian: AccountId,
+    pub houses: Vec<House>,
+    pub snapshot_contract: AccountId,
+    pub encryption_public_key: String,
+    pub results_config: ResultsConfig,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// *Transaction*: Deploys the new code of the contract and migrates the state
+    ///
+    /// The code is passed as the raw input of the call instead of JSON arguments.
+    /// The old code stays if the migration fails.
+    ///
+    /// Requirements:
+    /// - Only admin can upgrade the contract
+    pub fn upgrade(&self) -> Promise {
+        self.assert_admin();
+
+        deploy_and_migrate(MIGRATE_GAS)
+    }
+
+    /// *Transaction*: Backfills the index and the commitment of the votes stored by the first release
+    ///
+    /// One-off migration step: the votes are processed in batches after the migration from
+    /// the first release, as they don't fit into the gas of `migrate`. Returns the number
+    /// of the votes left. The backfill is over once it returns 0.
+    /// The new votes and the results can't be submitted until all votes are backfilled.
+    ///
+    /// Requirements:
+    /// - Only admin can backfill the votes
+    /// - The contract should be migrated from the first release and the backfill should not be over
+    /// - Up to `MAX_BACKFILL_BATCH` votes per call
+    pub fn backfill_votes(&mut self, limit: u64) -> u64 {
+        self.assert_admin();
+        let from = self
+            .votes_backfill
+            .unwrap_or_else(|| env::panic_str(NO_VOTES_BACKFILL));
+
+        let to = std::cmp::min(
+            from + std::cmp::min(limit, MAX_BACKFILL_BATCH),
+            self.votes.len(),
+        );
+        for index in from..to {
+            let vote = self.votes.get(index).unwrap();
+            // The first release didn't reject the duplicates, the first vote keeps the index
+            let hash = vote.hash();
+            if !self.vote_indices.contains_key(&hash) {
+                self.vote_indices.insert(&hash, &index);
+            }
+            self.votes_commitment = crate::crypto::chain_vote(&self.votes_commitment, &vote);
+        }
+        self.votes_backfill = if to < self.votes.len() {
+            Some(to)
+        } else {
+            None
+        };
+
+        self.votes.len() - to
+    }
+
+    /// *Callback*: Migrates the state of the previous version after the upgrade
+    ///
+    /// Decodes the layout of the stored state version and applies the migrations
+    /// from it to the current version one by one. The current layout is kept as is.
+    /// When the layout changes, the current one is copied as the next `ContractV<n>`
+    /// with a step from it, and `STATE_VERSION` is bumped.
+    ///
+    /// The first release is upgraded with a deploy and a `migrate` call from the contract account,
+    /// passing `v1_config` with the config that it didn't store. It's ignored for the later versions.
+    ///
+    /// Requirements:
+    /// - `v1_config` is required for the migration from the first version
+    /// - The admin and the guardian from `v1_config` should differ from the relayer
+    ///
+    /// Private function
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate(v1_config: Option<V1MigrationConfig>) -> Self {
+        let contract = match read_state_version() {
+            1 => Self::migrate_v1(
+                read_state(),
+                v1_config.unwrap_or_else(|| env::panic_str(NO_V1_MIGRATION_CONFIG)),
+            ),
+            STATE_VERSION => read_state(),
+            _ => env::panic_str(UNKNOWN_STATE_VERSION),
+        };
+        write_state_version(STATE_VERSION);
+
+        contract
+    }
+}
+
+fn read_state<T: BorshDeserialize>() -> T {
+    env::state_read().unwrap_or_else(|| env::panic_str(NO_STATE))
+}
+
+impl Contract {
+    /// Migrates the state from the layout version 1 to 2
+    fn migrate_v1(old: ContractV1, config: V1MigrationConfig) -> Self {
+        require!(
+            config.admin != old.relayer && config.guardian != old.relayer,
+            RELAYER_SHARED_ROLE
+        );
+        require!(House::are_valid(&config.houses), INVALID_HOUSES);
+        let encryption_public_key = Self::decode_encryption_key(&config.encryption_public_key);
+        let (candidate_weights, tally_weights) = Self::init_house_weights(&config.houses);
+
+        // The results of the first release are not split by the houses and don't reference
+        // the votes, so they are dropped and the relayer submits them again
+        let mut old_weights = old.candidate_weights;
+        old_weights.clear();
+
+        let votes_backfill = if old.votes.is_empty() { None } else { Some(0) };
+        // The first release started the voting on init
+        let status = if env::block_timestamp_ms() < old.end_time_in_ms {
+            VotingStatus::Voting
+        } else {
+            VotingStatus::Tallying
+        };
+
+        Self {
+            votes: old.votes,
+            // The stored votes are indexed and chained in batches with `backfill_votes`
+            vote_indices: LookupMap::new(StorageKey::VoteIndices),
+            votes_commitment: [0; 32],
+            votes_backfill,
+            houses: config.houses,
+            candidate_weights,
+            admin: config.admin,
+            pending_admin: None,
+            relayer: old.relayer,
+            guardian: config.guardian,
+            paused_at: None,
+            status: status.into(),
+            // Only used to start the voting, which is already started
+            voting_duration_in_ms: 0,
+            end_time_in_ms: old.end_time_in_ms,
+            results_config: config.results_config.into(),
+            results_challenge_end: None,
+            relayer_bond: NearToken::from_yoctonear(0),
+            disputes: Vector::new(StorageKey::Disputes),
+            snapshot_contract: config.snapshot_contract,
+            encryption_public_key,
+            onchain_tally: false,
+            processed_votes: 0,
+            processing_batch: None,
+            counted_ballots: LookupMap::new(StorageKey::CountedBallots),
+            tally_weights,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        mock::MockAction,
+        test_utils::{get_created_receipts, VMContextBuilder},
+        testing_env, CryptoHash, VMContext,
+    };
+
+    use super::*;
+    use crate::crypto;
+    use crate::test_utils::*;
+    use crate::types::VotingPackage;
+    use crate::versioned::VersionedVotingStatus;
+
+    // Storage prefix of the candidate weights in the first release
+    const V1_CANDIDATES_WEIGHTS: &[u8] = &[1];
+
+    fn v1_config() -> V1MigrationConfig {
+        V1MigrationConfig {
+            admin: admin(),
+            guardian: guardian(),
+            houses: houses(),
+            snapshot_contract: snapshot(),
+            encryption_public_key: encryption_public_key(),
+            results_config: results_config(),
+        }
+    }
+
+    // Writes the state of the first release with the given ballots and the results
+    // submitted by the relayer
+    fn write_v1_state(context: &VMContext, ballots: &[VotingPackage]) {
+        testing_env!(context.clone());
+
+        let mut votes = Vector::new(StorageKey::Votes);
+        for (i, ballot) in ballots.iter().enumerate() {
+            let vote: Option<EncryptedVoteStorage> = encrypt(ballot, i as u8 + 100).into();
+            votes.push(&vote.unwrap());
+        }
+        let mut candidate_weights = UnorderedMap::new(V1_CANDIDATES_WEIGHTS);
+        candidate_weights.insert(&acc(2), &10);
+
+        env::state_write(&ContractV1 {
+            votes,
+            candidate_weights,
+            relayer: relayer(),
+            end_time_in_ms: end_time(),
+        });
+    }
+
+    fn v1_ballots() -> Vec<VotingPackage> {
+        vec![
+            ballot(1, vec![(acc(10), 10)]),
+            ballot(2, vec![(acc(11), 5)]),
+        ]
+    }
+
+    fn backfill(context: &mut VMContext, contract: &mut Contract, limit: u64) -> u64 {
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.backfill_votes(limit)
+    }
+
+    fn chain_votes(contract: &Contract) -> CryptoHash {
+        contract.votes.iter().fold([0; 32], |commitment, vote| {
+            crypto::chain_vote(&commitment, &vote)
+        })
+    }
+
+    fn migrate_v1(context: &mut VMContext) -> Contract {
+        context.block_timestamp = START * MSECOND;
+        write_v1_state(context, &v1_ballots());
+
+        Contract::migrate(Some(v1_config()))
+    }
+
+    #[test]
+    fn v1_state_is_migrated() {
+        let mut context = VMContextBuilder::new().build();
+        let mut contract = migrate_v1(&mut context);
+
+        assert_eq!(read_state_version(), STATE_VERSION);
+        assert!(matches!(contract.status, VersionedVotingStatus::V1(_)));
+        assert_eq!(contract.get_status(), VotingStatus::Voting);
+        assert_eq!(contract.get_end_time(), end_time());
+        assert_eq!(contract.get_relayer(), relayer());
+        assert_eq!(contract.get_admin(), admin());
+        assert_eq!(contract.get_guardian(), guardian());
+        assert_eq!(contract.get_houses(), houses());
+        assert_eq!(contract.get_results_config(), results_config());
+        assert_eq!(contract.get_total_votes(), 2);
+        assert!(!contract.is_paused());
+
+        // The results of the first release are dropped
+        assert!(contract.get_candidate_weights(house(), 0, 10).is_empty());
+        let old_weights: UnorderedMap<AccountId, u64> = UnorderedMap::new(V1_CANDIDATES_WEIGHTS);
+        assert_eq!(old_weights.get(&acc(2)), None);
+
+        // The votes are backfilled in batches after the migration
+        let vote: EncryptedVoteStorage = contract.votes.get(1).unwrap();
+        assert_eq!(contract.get_vote_index(vote.hash().into()), None);
+        assert_eq!(backfill(&mut context, &mut contract, 1), 1);
+        assert_eq!(backfill(&mut context, &mut contract, 10), 0);
+        assert_eq!(contract.get_vote_index(vote.hash().into()), Some(1));
+        assert_eq!(contract.votes_commitment, chain_votes(&contract));
+
+        // The migrated votes can be tallied
+        context.block_timestamp = (end_time() + 1) * MSECOND;
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.try_move_stage();
+        assert_eq!(contract.get_status(), VotingStatus::Tallying);
+        process_batch(&mut context, &mut contract, 0, 2, Ok(voters_info()));
+        assert_eq!(contract.get_processed_votes(), 2);
+    }
+
+    #[test]
+    fn v1_state_after_voting_is_migrated_to_tallying() {
+        let mut context = VMContextBuilder::new().build();
+        context.block_timestamp = (end_time() + 1) * MSECOND;
+        write_v1_state(&context, &v1_ballots());
+
+        let contract = Contract::migrate(Some(v1_config()));
+
+        assert_eq!(contract.get_status(), VotingStatus::Tallying);
+    }
+
+    #[test]
+    #[should_panic(expected = "Migration from the first version expects the config")]
+    fn v1_state_is_not_migrated_without_config() {
+        let context = VMContextBuilder::new().build();
+        write_v1_state(&context, &v1_ballots());
+
+        Contract::migrate(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Admin and guardian should differ from the relayer")]
+    fn relayer_cannot_become_admin_on_migration() {
+        let context = VMContextBuilder::new().build();
+        write_v1_state(&context, &v1_ballots());
+
+        Contract::migrate(Some(V1MigrationConfig {
+            admin: relayer(),
+            ..v1_config()
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Admin and guardian should differ from the relayer")]
+    fn relayer_cannot_become_guardian_on_migration() {
+        let context = VMContextBuilder::new().build();
+        write_v1_state(&context, &v1_ballots());
+
+        Contract::migrate(Some(V1MigrationConfig {
+            guardian: relayer(),
+            ..v1_config()
+        }));
+    }
+
+    #[test]
+    fn state_is_migrated_twice() {
+        let mut context = VMContextBuilder::new().build();
+        let mut contract = migrate_v1(&mut context);
+        assert_eq!(backfill(&mut context, &mut contract, 1), 1);
+
+        // The next upgrade reads the current layout instead of the first one
+        env::state_write(&contract);
+        let mut contract = Contract::migrate(None);
+
+        assert_eq!(read_state_version(), STATE_VERSION);
+        assert_eq!(contract.get_status(), VotingStatus::Voting);
+        assert_eq!(contract.get_results_config(), results_config());
+        assert_eq!(contract.get_total_votes(), 2);
+        assert_eq!(contract.get_admin(), admin());
+        assert_eq!(contract.get_guardian(), guardian());
+
+        // The backfill resumes after the upgrade
+        assert_eq!(contract.votes_backfill, Some(1));
+        assert_eq!(backfill(&mut context, &mut contract, 10), 0);
+        assert_eq!(contract.votes_backfill, None);
+        let vote: EncryptedVoteStorage = contract.votes.get(1).unwrap();
+        assert_eq!(contract.get_vote_index(vote.hash().into()), Some(1));
+        assert_eq!(contract.votes_commitment, chain_votes(&contract));
+    }
+
+    #[test]
+    #[should_panic(expected = "Stored votes are not backfilled yet")]
+    fn votes_cant_be_sent_during_backfill() {
+        let mut context = VMContextBuilder::new().build();
+        let mut contract = migrate_v1(&mut context);
+
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.send_encrypted_votes(vec![encrypt(&ballot(3, vec![(acc(10), 1)]), 200)]);
+    }
+
+    #[test]
+    fn votes_sent_after_backfill_are_chained_in_order() {
+        let mut context = VMContextBuilder::new().build();
+        let mut contract = migrate_v1(&mut context);
+        assert_eq!(backfill(&mut context, &mut contract, 10), 0);
+
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.send_encrypted_votes(vec![encrypt(&ballot(3, vec![(acc(10), 1)]), 200)]);
+
+        let vote: EncryptedVoteStorage = contract.votes.get(2).unwrap();
+        assert_eq!(contract.get_vote_index(vote.hash().into()), Some(2));
+        assert_eq!(contract.votes_commitment, chain_votes(&contract));
+    }
+
+    #[test]
+    #[should_panic(expected = "Stored votes are not backfilled yet")]
+    fn cant_submit_results_before_backfill() {
+        let mut context = VMContextBuilder::new().build();
+        let mut contract = migrate_v1(&mut context);
+        backfill(&mut context, &mut contract, 1);
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "No votes to backfill")]
+    fn votes_are_not_backfilled_twice() {
+        let mut context = VMContextBuilder::new().build();
+        let mut contract = migrate_v1(&mut context);
+        assert_eq!(backfill(&mut context, &mut contract, 10), 0);
+
+        backfill(&mut context, &mut contract, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "No votes to backfill")]
+    fn new_contract_has_no_votes_to_backfill() {
+        let (mut context, mut contract) = setup_ctr();
+
+        backfill(&mut context, &mut contract, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only admin can call this method")]
+    fn non_admin_cannot_backfill_votes() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = relayer();
+        testing_env!(context.clone());
+        contract.backfill_votes(10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown state version")]
+    fn unknown_state_version_is_not_migrated() {
+        let (_context, contract) = setup_ctr();
+        env::state_write(&contract);
+        write_state_version(STATE_VERSION + 1);
+
+        Contract::migrate(None);
+    }
+
+    #[test]
+    fn admin_can_upgrade_contract() {
+        let (mut context, contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.input = vec![1, 2, 3];
+        testing_env!(context.clone());
+        contract.upgrade();
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert!(matches!(
+            &receipts[0].actions[..],
+            [
+                MockAction::DeployContract { code, .. },
+                MockAction::FunctionCallWeight { method_name, args, .. },
+            ] if code == &vec![1, 2, 3] && method_name == b"migrate" && args == b"{}"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only admin can call this method")]
+    fn relayer_cannot_upgrade_contract() {
+        let (mut context, contract) = setup_ctr();
+
+        context.predecessor_account_id = relayer();
+        context.input = vec![1, 2, 3];
+        testing_env!(context.clone());
+        contract.upgrade();
+    }
+}
@@ -0,0 +1,206 @@
+use crate::events::{emit_paused, emit_unpaused};
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// *Transaction*: Pauses the state-changing methods of the contract in emergencies
+    ///
+    /// The upgrade stays available, so the issue can be fixed while the contract is paused.
+    ///
+    /// Requirements:
+    /// - Only guardian can pause the contract
+    /// - The contract should not be paused
+    pub fn pause(&mut self) {
+        self.assert_guardian();
+        // The voting that is already over shouldn't be extended by the pause
+        self.try_move_stage();
+
+        self.paused_at = Some(env::block_timestamp_ms());
+        emit_paused(&self.guardian);
+    }
+
+    /// *Transaction*: Unpauses the contract.
+    /// The end of the voting and of the results challenge are shifted by the pause duration.
+    ///
+    /// Requirements:
+    /// - Only guardian can unpause the contract
+    /// - The contract should be paused
+    pub fn unpause(&mut self) {
+        self.assert_guardian();
+        let paused_at = self
+            .paused_at
+            .take()
+            .unwrap_or_else(|| panic_str(NOT_PAUSED));
+
+        let paused_for = env::block_timestamp_ms().saturating_sub(paused_at);
+        if *self.status == VotingStatus::Voting {
+            self.end_time_in_ms += paused_for;
+        }
+        if let Some(end) = self.results_challenge_end.as_mut() {
+            if *end >= paused_at {
+                *end += paused_for;
+            }
+        }
+        emit_unpaused(&self.guardian, paused_for);
+    }
+
+    /// *Transaction*: Sets the new guardian, e.g. if the guardian key is lost or compromised
+    ///
+    /// Requirements:
+    /// - Only admin can set the new guardian
+    pub fn set_guardian(&mut self, guardian: AccountId) {
+        self.assert_admin();
+
+        self.guardian = guardian;
+    }
+
+    fn assert_guardian(&self) {
+        require!(
+            env::predecessor_account_id() == self.guardian,
+            GUARDIAN_ONLY
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::get_logs, testing_env, VMContext};
+
+    use crate::test_utils::*;
+    use crate::*;
+
+    fn pause(context: &mut VMContext, contract: &mut Contract) {
+        context.predecessor_account_id = guardian();
+        testing_env!(context.clone());
+        contract.pause();
+    }
+
+    fn unpause_after(context: &mut VMContext, contract: &mut Contract, paused_for: u64) {
+        context.predecessor_account_id = guardian();
+        context.block_timestamp += paused_for * MSECOND;
+        testing_env!(context.clone());
+        contract.unpause();
+    }
+
+    #[test]
+    fn guardian_can_pause_and_unpause_voting() {
+        let (mut context, mut contract) = setup_ctr();
+        let end_time = contract.get_end_time();
+
+        pause(&mut context, &mut contract);
+        assert!(contract.is_paused());
+        assert_eq!(
+            contract.get_paused_at(),
+            Some(context.block_timestamp / MSECOND)
+        );
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"paused","data":{"by":"guardian.near"}}"#
+            ]
+        );
+
+        unpause_after(&mut context, &mut contract, 100);
+        assert!(!contract.is_paused());
+        assert_eq!(contract.get_end_time(), end_time + 100);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-voting","version":"1.0.0","event":"unpaused","data":{"by":"guardian.near","paused_for_ms":100}}"#
+            ]
+        );
+
+        // The votes are accepted for the pause duration longer
+        context.block_timestamp = (end_time + 1) * MSECOND;
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(2), 10)])],
+        );
+        assert_eq!(contract.get_total_votes(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn paused_contract_rejects_votes() {
+        let (mut context, mut contract) = setup_ctr();
+
+        pause(&mut context, &mut contract);
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(2), 10)])],
+        );
+    }
+
+    #[test]
+    fn pause_does_not_extend_finished_voting() {
+        let (mut context, mut contract) = setup_ctr();
+        let end_time = contract.get_end_time();
+
+        context.block_timestamp = (end_time + 1) * MSECOND;
+        pause(&mut context, &mut contract);
+        assert_eq!(contract.get_status(), VotingStatus::Tallying);
+
+        unpause_after(&mut context, &mut contract, 100);
+        assert_eq!(contract.get_end_time(), end_time);
+    }
+
+    #[test]
+    fn unpause_extends_results_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        let challenge_end = contract.get_results_challenge_end().unwrap();
+
+        pause(&mut context, &mut contract);
+        unpause_after(&mut context, &mut contract, 100);
+
+        assert_eq!(
+            contract.get_results_challenge_end(),
+            Some(challenge_end + 100)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only guardian can call this method")]
+    fn relayer_cant_pause() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = relayer();
+        testing_env!(context.clone());
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is not paused")]
+    fn cant_unpause_active_contract() {
+        let (mut context, mut contract) = setup_ctr();
+
+        unpause_after(&mut context, &mut contract, 0);
+    }
+
+    #[test]
+    fn admin_can_set_guardian() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.set_guardian(acc(9));
+        assert_eq!(contract.get_guardian(), acc(9));
+
+        context.predecessor_account_id = acc(9);
+        testing_env!(context.clone());
+        contract.pause();
+        assert!(contract.is_paused());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only admin can call this method")]
+    fn guardian_cannot_set_guardian() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = guardian();
+        testing_env!(context.clone());
+        contract.set_guardian(acc(9));
+    }
+}
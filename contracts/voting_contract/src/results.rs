@@ -0,0 +1,527 @@
+use std::collections::BTreeMap;
+
+use common_contracts::finalize_storage_check_with_reserve;
+use near_sdk::json_types::Base58CryptoHash;
+
+use crate::events::{emit_results_finalized, emit_results_submitted};
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Submits the results of all houses and opens the results challenge.
+    ///
+    /// The relayer posts the bond that is returned once the results are finalized.
+    /// The results are bound to the set of the counted votes by the votes commitment.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only relayer can submit the results
+    /// - Voting phase should be over
+    /// - Results shouldn't be submitted or finalized before
+    /// - All stored votes should be backfilled into the votes commitment after the upgrade
+    /// - `votes_commitment` should match `get_votes_commitment`
+    /// - Relayer should attach the bond and pay for the storage
+    #[payable]
+    pub fn sumbit_results(
+        &mut self,
+        results: Vec<(String, Vec<(AccountId, u64)>)>,
+        votes_commitment: Base58CryptoHash,
+    ) {
+        let storage_start = env::storage_usage();
+
+        self.try_move_stage();
+        match *self.status {
+            VotingStatus::Tallying => {}
+            VotingStatus::NotStarted | VotingStatus::Voting => panic_str(VOTING_PHASE_IN_PROGRESS),
+            VotingStatus::ResultsPublished => panic_str(RESULTS_ALREADY_SUBMITTED),
+            VotingStatus::Finalized => panic_str(RESULTS_FINALIZED),
+        }
+        self.assert_relayer();
+        require!(self.votes_backfill.is_none(), VOTES_BACKFILL_IN_PROGRESS);
+        require!(
+            CryptoHash::from(votes_commitment) == self.votes_commitment,
+            VOTES_COMMITMENT_MISMATCH
+        );
+
+        for (house, results) in results {
+            let mut weights = self.house_weights(&house);
+            weights.extend(results);
+            self.candidate_weights.insert(&house, &weights);
+        }
+
+        self.relayer_bond = self.results_config.bond;
+        let challenge_end = env::block_timestamp_ms() + self.results_config.challenge_timeout_in_ms;
+        self.results_challenge_end = Some(challenge_end);
+        *self.status = VotingStatus::ResultsPublished;
+        emit_phase_change(*self.status);
+        emit_results_submitted(
+            &self.relayer,
+            self.relayer_bond,
+            challenge_end,
+            votes_commitment,
+        );
+
+        require!(
+            finalize_storage_check_with_reserve(storage_start, 0, self.results_config.bond),
+            DEPOSIT_NOT_ENOUGH
+        );
+    }
+
+    /// Disputes the total of the candidate submitted by the relayer.
+    ///
+    /// The disputer carries the burden of proof: the dispute is upheld only if the on-chain
+    /// tally is completed by the end of the challenge and contradicts the submitted total.
+    /// `vote_indices` optionally point to the miscounted votes, so anyone can check them before
+    /// running the tally. They don't affect how the dispute is decided.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Results challenge should be in progress
+    /// - Vote indices, if any, should point to the stored votes
+    /// - User should attach the dispute deposit and pay for the storage
+    #[payable]
+    pub fn dispute_results(&mut self, house: String, candidate: AccountId, vote_indices: Vec<u64>) {
+        let storage_start = env::storage_usage();
+
+        self.assert_not_paused();
+        require!(
+            *self.status == VotingStatus::ResultsPublished
+                && self
+                    .results_challenge_end
+                    .map_or(false, |end| env::block_timestamp_ms() <= end),
+            NOT_ON_RESULTS_CHALLENGE
+        );
+        self.house_weights(&house);
+        require!(
+            vote_indices.iter().all(|i| *i < self.votes.len()),
+            INVALID_VOTE_INDICES
+        );
+        require!(self.disputes.len() < MAX_DISPUTES, TOO_MANY_DISPUTES);
+
+        let disputer = env::predecessor_account_id();
+        require!(
+            !self
+                .disputes
+                .iter()
+                .any(|dispute| dispute.disputer == disputer
+                    && dispute.house == house
+                    && dispute.candidate == candidate),
+            ALREADY_DISPUTED
+        );
+
+        self.disputes.push(&Dispute {
+            disputer,
+            house,
+            candidate,
+            vote_indices,
+            deposit: self.results_config.dispute_deposit,
+        });
+
+        require!(
+            finalize_storage_check_with_reserve(
+                storage_start,
+                0,
+                self.results_config.dispute_deposit
+            ),
+            DEPOSIT_NOT_ENOUGH
+        );
+    }
+
+    /// Finalizes the results once the challenge is over.
+    ///
+    /// The status moves to `Finalized`, or back to `Tallying` if the results are discarded.
+    ///
+    /// - Without the completed on-chain tally, the disputes are unresolved and their deposits are refunded.
+    /// - Deposits of the disputes confirmed by the completed tally go to the relayer.
+    /// - If no dispute is upheld, the relayer results are finalized and the bond is returned.
+    /// - Otherwise, the bond is split between the disputers contradicted by the completed tally,
+    ///   the relayer results are discarded, and the tallying is reopened.
+    /// - Without the relayer results, the completed on-chain tally of at least one vote
+    ///   becomes the final results.
+    pub fn finalize_results(&mut self) {
+        self.try_move_stage();
+        self.assert_counting();
+        require!(!self.is_processing_batch(), TALLY_IN_PROGRESS);
+
+        if *self.status == VotingStatus::Tallying {
+            require!(!self.votes.is_empty(), NO_VOTES_TO_TALLY);
+            require!(self.is_tally_completed(), TALLY_NOT_COMPLETED);
+            for house in self.houses.iter() {
+                let mut weights = self.house_weights(&house.name);
+                weights.extend(self.house_tally(&house.name).iter());
+                self.candidate_weights.insert(&house.name, &weights);
+            }
+            self.onchain_tally = true;
+            *self.status = VotingStatus::Finalized;
+            emit_phase_change(*self.status);
+            emit_results_finalized(true, 0);
+            return;
+        }
+        require!(
+            self.results_challenge_end
+                .map_or(false, |end| env::block_timestamp_ms() > end),
+            RESULTS_CHALLENGE_IN_PROGRESS
+        );
+
+        let disputes = self.disputes.to_vec();
+        self.disputes.clear();
+
+        let mut payouts: BTreeMap<AccountId, NearToken> = BTreeMap::new();
+        let mut pay = |account: &AccountId, amount: NearToken| {
+            let total = payouts.entry(account.clone()).or_default();
+            *total = total.saturating_add(amount);
+        };
+
+        let (refuted, upheld): (Vec<_>, Vec<_>) = if self.is_tally_completed() {
+            disputes.into_iter().partition(|dispute| {
+                let submitted = self.house_weights(&dispute.house).get(&dispute.candidate);
+                let computed = self.house_tally(&dispute.house).get(&dispute.candidate);
+                submitted.unwrap_or_default() == computed.unwrap_or_default()
+            })
+        } else {
+            for dispute in disputes.iter() {
+                pay(&dispute.disputer, dispute.deposit);
+            }
+            (vec![], vec![])
+        };
+
+        for dispute in refuted.iter() {
+            pay(&self.relayer, dispute.deposit);
+        }
+
+        if upheld.is_empty() {
+            pay(&self.relayer, self.relayer_bond);
+            *self.status = VotingStatus::Finalized;
+        } else {
+            let share = self.relayer_bond.saturating_div(upheld.len() as u128);
+            for dispute in upheld.iter() {
+                pay(&dispute.disputer, dispute.deposit.saturating_add(share));
+            }
+
+            for house in self.houses.iter() {
+                let mut weights = self.house_weights(&house.name);
+                weights.clear();
+                self.candidate_weights.insert(&house.name, &weights);
+            }
+            *self.status = VotingStatus::Tallying;
+        }
+        emit_phase_change(*self.status);
+        if upheld.is_empty() {
+            emit_results_finalized(false, refuted.len() as u64);
+        }
+        self.relayer_bond = NearToken::from_yoctonear(0);
+        self.results_challenge_end = None;
+
+        for (account, amount) in payouts {
+            if !amount.is_zero() {
+                Promise::new(account).transfer(amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_contracts::events::{parse_event_log, voting::VotingEvent};
+    use near_sdk::{
+        mock::MockAction,
+        test_utils::{get_created_receipts, get_logs},
+        testing_env, AccountId, NearToken, VMContext,
+    };
+
+    use crate::{test_utils::*, Contract};
+
+    fn transferred_to(account: &AccountId) -> NearToken {
+        get_created_receipts()
+            .iter()
+            .filter(|receipt| &receipt.receiver_id == account)
+            .flat_map(|receipt| receipt.actions.iter())
+            .map(|action| match action {
+                MockAction::Transfer { deposit, .. } => *deposit,
+                _ => NearToken::from_yoctonear(0),
+            })
+            .fold(NearToken::from_yoctonear(0), NearToken::saturating_add)
+    }
+
+    fn dispute(context: &mut VMContext, contract: &mut Contract, disputer: AccountId) {
+        context.predecessor_account_id = disputer;
+        context.attached_deposit = NearToken::from_near(2);
+        testing_env!(context.clone());
+
+        contract.dispute_results(house(), acc(10), vec![0]);
+    }
+
+    fn move_after_challenge(context: &mut VMContext, contract: &Contract) {
+        context.block_timestamp = (contract.get_results_challenge_end().unwrap() + 1) * MSECOND;
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_yoctonear(0);
+        testing_env!(context.clone());
+    }
+
+    #[test]
+    fn submission_opens_results_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+
+        let challenge_end = end_time() + 1 + results_config().challenge_timeout_in_ms;
+        assert_eq!(contract.get_results_challenge_end(), Some(challenge_end));
+        assert_eq!(
+            get_logs()
+                .iter()
+                .filter_map(|log| parse_event_log::<VotingEvent>(log))
+                .last(),
+            Some(VotingEvent::ResultsSubmitted {
+                bond: results_config().bond,
+                challenge_end,
+                relayer: relayer(),
+                votes_commitment: contract.get_votes_commitment(),
+            })
+        );
+        assert!(!contract.is_results_finalized());
+        assert_eq!(
+            contract.get_candidate_weights(house(), 0, 10),
+            vec![(acc(10), 10)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Results are already submitted")]
+    fn relayer_cant_overwrite_results() {
+        let (mut context, mut contract) = setup_ctr();
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        submit_results(&mut context, &mut contract, vec![(acc(10), 100)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit is not enough to cover the storage cost")]
+    fn relayer_should_post_bond() {
+        let (mut context, mut contract) = setup_ctr();
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(1);
+        context.block_timestamp = (end_time() + 1) * MSECOND;
+        testing_env!(context.clone());
+
+        contract.sumbit_results(
+            vec![(house(), vec![(acc(10), 10)])],
+            contract.get_votes_commitment(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Results should reference the final votes commitment")]
+    fn results_should_reference_votes_commitment() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(2), 10)])],
+        );
+
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(11);
+        testing_env!(context.clone());
+        contract.sumbit_results(vec![(house(), vec![(acc(2), 10)])], [0; 32].into());
+    }
+
+    #[test]
+    fn results_are_finalized_without_disputes() {
+        let (mut context, mut contract) = setup_ctr();
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        move_after_challenge(&mut context, &contract);
+
+        contract.finalize_results();
+
+        assert!(contract.is_results_finalized());
+        assert_eq!(
+            get_logs()
+                .iter()
+                .filter_map(|log| parse_event_log::<VotingEvent>(log))
+                .last(),
+            Some(VotingEvent::ResultsFinalized {
+                from_tally: false,
+                refuted_disputes: 0,
+            })
+        );
+        assert_eq!(transferred_to(&relayer()), results_config().bond);
+        assert_eq!(contract.get_winners(house()), vec![(acc(10), 10)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Results challenge is in progress")]
+    fn cant_finalize_during_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+
+        contract.finalize_results();
+    }
+
+    #[test]
+    fn dispute_is_refuted_by_onchain_tally() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        dispute(&mut context, &mut contract, acc(5));
+        assert_eq!(contract.get_disputes(0, 10).len(), 1);
+
+        process_batch(&mut context, &mut contract, 0, 10, Ok(voters_info()));
+        move_after_challenge(&mut context, &contract);
+
+        contract.finalize_results();
+
+        assert!(contract.is_results_finalized());
+        assert!(!contract.is_onchain_tally());
+        assert!(contract.get_disputes(0, 10).is_empty());
+        assert_eq!(
+            transferred_to(&relayer()),
+            results_config()
+                .bond
+                .saturating_add(results_config().dispute_deposit)
+        );
+        assert_eq!(transferred_to(&acc(5)), NearToken::from_yoctonear(0));
+    }
+
+    #[test]
+    fn upheld_dispute_slashes_bond_and_reopens_tallying() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 100)]);
+        dispute(&mut context, &mut contract, acc(5));
+        dispute(&mut context, &mut contract, acc(6));
+
+        process_batch(&mut context, &mut contract, 0, 10, Ok(voters_info()));
+        move_after_challenge(&mut context, &contract);
+
+        contract.finalize_results();
+
+        assert!(!contract.is_results_finalized());
+        assert_eq!(contract.get_results_challenge_end(), None);
+        assert_eq!(contract.get_total_candidates(house()), 0);
+        assert_eq!(transferred_to(&acc(5)), NearToken::from_near(6));
+        assert_eq!(transferred_to(&acc(6)), NearToken::from_near(6));
+        assert_eq!(transferred_to(&relayer()), NearToken::from_yoctonear(0));
+
+        // The completed on-chain tally becomes the final results
+        contract.finalize_results();
+
+        assert!(contract.is_results_finalized());
+        assert!(contract.is_onchain_tally());
+        assert_eq!(contract.get_winners(house()), vec![(acc(10), 10)]);
+    }
+
+    #[test]
+    fn dispute_is_refunded_if_tally_is_not_completed() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 100)]);
+        dispute(&mut context, &mut contract, acc(5));
+        move_after_challenge(&mut context, &contract);
+
+        contract.finalize_results();
+
+        assert!(contract.is_results_finalized());
+        assert!(contract.get_disputes(0, 10).is_empty());
+        assert_eq!(contract.get_winners(house()), vec![(acc(10), 100)]);
+        assert_eq!(transferred_to(&acc(5)), results_config().dispute_deposit);
+        assert_eq!(transferred_to(&relayer()), results_config().bond);
+    }
+
+    #[test]
+    #[should_panic(expected = "Allowed only during results challenge")]
+    fn cant_dispute_after_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        move_after_challenge(&mut context, &contract);
+
+        dispute(&mut context, &mut contract, acc(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid vote indices")]
+    fn cant_dispute_with_unknown_votes() {
+        let (mut context, mut contract) = setup_ctr();
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        dispute(&mut context, &mut contract, acc(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Already disputed")]
+    fn cant_dispute_twice() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+        dispute(&mut context, &mut contract, acc(5));
+        dispute(&mut context, &mut contract, acc(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "On-chain tally is not completed")]
+    fn cant_finalize_without_results() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+
+        contract.finalize_results();
+    }
+
+    #[test]
+    #[should_panic(expected = "No votes to tally")]
+    fn cant_finalize_without_votes() {
+        let (mut context, mut contract) = setup_ctr();
+        context.block_timestamp = (end_time() + 1) * MSECOND;
+        testing_env!(context.clone());
+
+        contract.finalize_results();
+    }
+
+    #[test]
+    fn vote_indices_are_optional_for_dispute() {
+        let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(10), 10)])],
+        );
+        submit_results(&mut context, &mut contract, vec![(acc(10), 10)]);
+
+        context.predecessor_account_id = acc(5);
+        context.attached_deposit = NearToken::from_near(2);
+        testing_env!(context.clone());
+        contract.dispute_results(house(), acc(10), vec![]);
+
+        assert_eq!(contract.get_disputes(0, 10).len(), 1);
+    }
+}
@@ -5,5 +5,11 @@ use near_sdk::BorshStorageKey;
 #[borsh(crate = "near_sdk::borsh")]
 pub enum StorageKey {
     Votes,
+    VoteIndices,
     CandidatesWeights,
+    HouseCandidatesWeights { house: String },
+    CountedBallots,
+    TallyWeights,
+    HouseTallyWeights { house: String },
+    Disputes,
 }
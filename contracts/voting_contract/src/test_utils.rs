@@ -1,8 +1,24 @@
 use std::str::FromStr;
 
-use near_sdk::{test_utils::VMContextBuilder, AccountId, VMContext};
+use aes_siv::{siv::Aes128Siv, KeyInit};
+use ed25519_dalek::{Signer, SigningKey};
+use near_sdk::{
+    test_utils::VMContextBuilder, testing_env, AccountId, CurveType, NearToken, PublicKey,
+    VMContext,
+};
+use secp256k1::{ecdh::SharedSecret, Secp256k1, SecretKey};
 
-use crate::Contract;
+use crate::consts::MAX_TALLY_BATCH;
+use crate::crypto;
+
+use crate::{
+    types::{
+        EncryptedVoteView, House, ResultsConfig, SnapshotStatus, Vote, VoterInformation,
+        VotingPackage,
+    },
+    Contract,
+};
+use near_sdk::PromiseError;
 
 /// 1ms in nano seconds
 pub const MSECOND: u64 = 1_000_000;
@@ -14,18 +30,246 @@ pub fn acc(idx: u8) -> AccountId {
     AccountId::from_str(&format!("user-{}.near", idx)).unwrap()
 }
 
+pub fn admin() -> AccountId {
+    AccountId::from_str("admin.near").unwrap()
+}
+
 pub fn relayer() -> AccountId {
     AccountId::from_str("relayer.near").unwrap()
 }
 
+pub fn guardian() -> AccountId {
+    AccountId::from_str("guardian.near").unwrap()
+}
+
+pub fn snapshot() -> AccountId {
+    AccountId::from_str("snapshot.near").unwrap()
+}
+
+pub fn houses() -> Vec<House> {
+    vec![
+        House {
+            name: "House of Merit".to_string(),
+            seats: 3,
+        },
+        House {
+            name: "Council of Advisors".to_string(),
+            seats: 1,
+        },
+    ]
+}
+
+pub fn house() -> String {
+    houses()[0].name.clone()
+}
+
 pub fn end_time() -> u64 {
     START + 500
 }
 
-pub fn setup_ctr() -> (VMContext, Contract) {
+pub fn results_config() -> ResultsConfig {
+    ResultsConfig {
+        bond: NearToken::from_near(10),
+        dispute_deposit: NearToken::from_near(1),
+        challenge_timeout_in_ms: 1000,
+    }
+}
+
+/// Submits the results of the first house as the relayer after the voting
+pub fn submit_results(
+    context: &mut VMContext,
+    contract: &mut Contract,
+    results: Vec<(AccountId, u64)>,
+) {
+    context.predecessor_account_id = relayer();
+    context.attached_deposit = NearToken::from_near(11);
+    context.block_timestamp = std::cmp::max(context.block_timestamp, (end_time() + 1) * MSECOND);
+    testing_env!(context.clone());
+
+    contract.sumbit_results(vec![(house(), results)], contract.get_votes_commitment());
+}
+
+const SECRET_KEY: [u8; 32] = [7; 32];
+
+/// The secret that is revealed by the secret contract after the voting
+pub fn secret_key() -> k256::SecretKey {
+    crypto::parse_secret_key(&SECRET_KEY).unwrap()
+}
+
+/// The same secret in libsecp256k1 that the relayer uses to encrypt the votes
+fn relayer_secret_key() -> SecretKey {
+    SecretKey::from_slice(&SECRET_KEY).unwrap()
+}
+
+pub fn encryption_public_key() -> String {
+    let public_key =
+        secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &relayer_secret_key());
+    bs58::encode(public_key.serialize()).into_string()
+}
+
+pub fn voter_key(idx: u8) -> SigningKey {
+    SigningKey::from_bytes(&[idx + 1; 32])
+}
+
+pub fn voter_pk(idx: u8) -> PublicKey {
+    PublicKey::from_parts(
+        CurveType::ED25519,
+        voter_key(idx).verifying_key().to_bytes().to_vec(),
+    )
+    .unwrap()
+}
+
+/// Builds the voting package signed by the voter the same way as the relayer's `VotingPackageBuilder`
+pub fn ballot(voter: u8, votes: Vec<(AccountId, u64)>) -> VotingPackage {
+    let mut package = VotingPackage {
+        account_id: acc(voter),
+        votes: votes
+            .into_iter()
+            .map(|(candidate, weight)| Vote { candidate, weight })
+            .collect(),
+        signature: String::new(),
+    };
+    let signature = voter_key(voter).sign(&package.signed_data());
+    package.signature = bs58::encode(signature.to_bytes()).into_string();
+    package
+}
+
+/// Encrypts the voting package with the ephemeral key the same way as the relayer's `encrypt`
+pub fn encrypt(package: &VotingPackage, ephemeral: u8) -> EncryptedVoteView {
+    let ephemeral = SecretKey::from_slice(&[ephemeral; 32]).unwrap();
+    let public_key =
+        secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &relayer_secret_key());
+    let shared_secret = SharedSecret::new(&public_key, &ephemeral);
+
+    let mut cipher = Aes128Siv::new(&shared_secret.secret_bytes().into());
+    let data = near_sdk::serde_json::to_vec(package).unwrap();
+    let vote = cipher.encrypt::<[&[u8]; 0], _>([], &data).unwrap();
+
+    EncryptedVoteView {
+        vote: bs58::encode(vote).into_string(),
+        pubkey: bs58::encode(
+            secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &ephemeral)
+                .serialize_uncompressed(),
+        )
+        .into_string(),
+    }
+}
+
+/// Initializes the contract that waits for the voting to be started
+pub fn init_ctr() -> (VMContext, Contract) {
     let context = VMContextBuilder::new().build();
 
-    let contract = Contract::new(relayer(), end_time());
+    let contract = Contract::new(
+        admin(),
+        relayer(),
+        guardian(),
+        end_time(),
+        houses(),
+        snapshot(),
+        encryption_public_key(),
+        results_config(),
+    );
+
+    (context, contract)
+}
+
+pub fn setup_ctr() -> (VMContext, Contract) {
+    let (mut context, mut contract) = init_ctr();
+    start_voting(
+        &mut context,
+        &mut contract,
+        Ok(SnapshotStatus::RegistrationEnded(0)),
+    );
 
     (context, contract)
 }
+
+/// Starts the voting and resolves the snapshot status call with the given status
+pub fn start_voting(
+    context: &mut VMContext,
+    contract: &mut Contract,
+    status: Result<SnapshotStatus, PromiseError>,
+) {
+    context.predecessor_account_id = relayer();
+    testing_env!(context.clone());
+    contract.start_voting();
+
+    context.predecessor_account_id = context.current_account_id.clone();
+    testing_env!(context.clone());
+    contract.on_snapshot_status(status);
+}
+
+pub fn send_votes(context: &mut VMContext, contract: &mut Contract, ballots: &[VotingPackage]) {
+    context.predecessor_account_id = relayer();
+    context.attached_deposit = NearToken::from_near(1);
+    testing_env!(context.clone());
+
+    let votes = ballots
+        .iter()
+        .enumerate()
+        .map(|(i, ballot)| encrypt(ballot, i as u8 + 100))
+        .collect();
+    contract.send_encrypted_votes(votes);
+
+    context.block_timestamp = (end_time() + 1) * MSECOND;
+    context.predecessor_account_id = acc(0);
+    testing_env!(context.clone());
+}
+
+pub fn secret() -> String {
+    bs58::encode(SECRET_KEY).into_string()
+}
+
+pub fn voters_info() -> Vec<(AccountId, VoterInformation)> {
+    (1..=3)
+        .map(|i| {
+            (
+                acc(i),
+                VoterInformation {
+                    vote_weight: 10,
+                    public_key: voter_pk(i),
+                },
+            )
+        })
+        .collect()
+}
+
+pub fn nominees_info() -> Vec<(AccountId, String)> {
+    vec![
+        (acc(10), houses()[0].name.clone()),
+        (acc(11), houses()[0].name.clone()),
+        (acc(12), houses()[1].name.clone()),
+    ]
+}
+
+/// Processes the batch and resolves the snapshot calls with the given data
+pub fn process_batch(
+    context: &mut VMContext,
+    contract: &mut Contract,
+    from: u64,
+    limit: u64,
+    voters: Result<Vec<(AccountId, VoterInformation)>, PromiseError>,
+) {
+    context.predecessor_account_id = acc(0);
+    testing_env!(context.clone());
+    contract.process_votes(secret(), from, limit);
+    assert!(contract.processing_batch.is_some());
+
+    let to = std::cmp::min(
+        from + std::cmp::min(limit, MAX_TALLY_BATCH),
+        contract.get_total_votes(),
+    );
+    let ballots = contract.decrypt_votes(&secret_key(), from, to);
+
+    context.predecessor_account_id = context.current_account_id.clone();
+    testing_env!(context.clone());
+    contract.on_votes_processed(
+        voters,
+        Ok(nominees_info()),
+        ballots,
+        from,
+        to,
+        acc(0),
+        context.attached_deposit,
+    );
+}
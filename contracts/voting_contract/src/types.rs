@@ -1,11 +1,54 @@
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
+    env,
     serde::{Deserialize, Serialize},
-    NearSchema,
+    AccountId, CryptoHash, NearSchema, NearToken, PublicKey, Timestamp,
 };
 
+use common_contracts::events::voting::VotingPhase;
+pub use common_contracts::house::House;
+
 type PubKey = [u8; 65];
 
+/// Phase of the elections
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Clone,
+    Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VotingStatus {
+    // Waiting for the voting to be started
+    NotStarted,
+    // Relayer sends the encrypted votes until the end time
+    Voting,
+    // Votes can't be sent anymore. Relayer computes the results and the on-chain tally can be run.
+    Tallying,
+    // Results are submitted by the relayer and can be disputed until the challenge ends
+    ResultsPublished,
+    // Results are final and can't be changed
+    Finalized,
+}
+
+impl VotingStatus {
+    pub fn event_info(&self) -> VotingPhase {
+        match self {
+            VotingStatus::NotStarted => VotingPhase::NotStarted,
+            VotingStatus::Voting => VotingPhase::Voting,
+            VotingStatus::Tallying => VotingPhase::Tallying,
+            VotingStatus::ResultsPublished => VotingPhase::ResultsPublished,
+            VotingStatus::Finalized => VotingPhase::Finalized,
+        }
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct EncryptedVoteStorage {
@@ -21,6 +64,14 @@ pub struct EncryptedVoteView {
     pub pubkey: String,
 }
 
+impl EncryptedVoteStorage {
+    /// sha256 of the bs58 ciphertext string followed by the ephemeral public key.
+    /// Identifies the stored vote in `get_vote_index` and in the `votes_submitted` event.
+    pub fn hash(&self) -> CryptoHash {
+        env::sha256_array(&[self.vote.as_bytes(), &self.pubkey].concat())
+    }
+}
+
 impl From<EncryptedVoteStorage> for EncryptedVoteView {
     fn from(vote: EncryptedVoteStorage) -> Self {
         Self {
@@ -38,3 +89,107 @@ impl From<EncryptedVoteView> for Option<EncryptedVoteStorage> {
         })
     }
 }
+
+/// Configuration of the relayer results challenge
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Clone,
+    Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ResultsConfig {
+    /// Bond the relayer posts with the results. Slashed if a dispute is not refuted.
+    pub bond: NearToken,
+    /// Deposit to dispute the results. Lost if the dispute is refuted.
+    pub dispute_deposit: NearToken,
+    pub challenge_timeout_in_ms: Timestamp,
+}
+
+/// Dispute of the candidate total submitted by the relayer
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Dispute {
+    pub disputer: AccountId,
+    pub house: String,
+    pub candidate: AccountId,
+    /// Indices of the votes that would change the total of the candidate.
+    /// Informational only: the dispute is decided by the on-chain tally of all votes.
+    pub vote_indices: Vec<u64>,
+    pub deposit: NearToken,
+}
+
+/// Phase of the snapshot contract, mirrors `Status` of the snapshot contract
+#[derive(Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SnapshotStatus {
+    Initialization(u32),
+    SnapshotChallenge(u32),
+    SnapshotHalted(u32),
+    Registration(u32),
+    RegistrationEnded(u32),
+}
+
+/// Vote weight and public key of the voter from the snapshot contract
+#[derive(Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VoterInformation {
+    pub vote_weight: u64,
+    pub public_key: PublicKey,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Vote {
+    pub candidate: AccountId,
+    pub weight: u64,
+}
+
+/// Decrypted voting package as it is built by the voter
+#[derive(Serialize, Deserialize, NearSchema, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VotingPackage {
+    pub account_id: AccountId,
+    pub votes: Vec<Vote>,
+    /// bs58 signature of the `{accountId, votes}` JSON
+    pub signature: String,
+}
+
+impl VotingPackage {
+    /// Data signed by the voter
+    pub fn signed_data(&self) -> Vec<u8> {
+        #[derive(Serialize)]
+        #[serde(crate = "near_sdk::serde")]
+        #[serde(rename_all = "camelCase")]
+        struct SignedData<'a> {
+            account_id: &'a AccountId,
+            votes: &'a [Vote],
+        }
+
+        near_sdk::serde_json::to_vec(&SignedData {
+            account_id: &self.account_id,
+            votes: &self.votes,
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// The vote of the counted ballot. Stored to replace the ballot with the later one.
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct CountedVote {
+    /// Index of the house in the houses configuration
+    pub house: u32,
+    pub candidate: AccountId,
+    pub weight: u64,
+}
@@ -0,0 +1,62 @@
+//! Versions of the types stored in the contract state.
+//!
+//! Each enum dereferences to the latest version of the type.
+//! Values of the older versions are converted to the latest one in `migrate`.
+
+use std::ops::{Deref, DerefMut};
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::types::{ResultsConfig, VotingStatus};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone, Copy)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedVotingStatus {
+    V1(VotingStatus),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone, Copy)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedResultsConfig {
+    V1(ResultsConfig),
+}
+
+impl From<VotingStatus> for VersionedVotingStatus {
+    fn from(status: VotingStatus) -> Self {
+        Self::V1(status)
+    }
+}
+
+impl Deref for VersionedVotingStatus {
+    type Target = VotingStatus;
+
+    fn deref(&self) -> &VotingStatus {
+        match self {
+            Self::V1(status) => status,
+        }
+    }
+}
+
+impl DerefMut for VersionedVotingStatus {
+    fn deref_mut(&mut self) -> &mut VotingStatus {
+        match self {
+            Self::V1(status) => status,
+        }
+    }
+}
+
+impl From<ResultsConfig> for VersionedResultsConfig {
+    fn from(config: ResultsConfig) -> Self {
+        Self::V1(config)
+    }
+}
+
+impl Deref for VersionedResultsConfig {
+    type Target = ResultsConfig;
+
+    fn deref(&self) -> &ResultsConfig {
+        match self {
+            Self::V1(config) => config,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use near_sdk::json_types::Base58CryptoHash;
+
 use crate::*;
 
 #[near_bindgen]
@@ -15,33 +17,161 @@ impl Contract {
         self.votes.len()
     }
 
-    pub fn get_candidate_weights(&self, page: u64, limit: u64) -> Vec<(AccountId, u64)> {
-        let start = std::cmp::min(page * limit, self.candidate_weights.len());
-        let end = std::cmp::min(start + limit, self.candidate_weights.len());
+    /// Returns the index of the stored vote by its hash: sha256 of the bs58 ciphertext string
+    /// followed by the ephemeral public key, as in the `votes_submitted` event
+    pub fn get_vote_index(&self, hash: Base58CryptoHash) -> Option<u64> {
+        self.vote_indices.get(&hash.into())
+    }
+
+    /// Returns if the vote with the hash is stored
+    pub fn has_vote(&self, hash: Base58CryptoHash) -> bool {
+        self.vote_indices.contains_key(&hash.into())
+    }
+
+    /// Returns the hash chain over the stored votes: starting from 32 zero bytes,
+    /// each vote appends sha256(commitment || vote hash).
+    /// The results submitted by the relayer should reference the final commitment.
+    pub fn get_votes_commitment(&self) -> Base58CryptoHash {
+        self.votes_commitment.into()
+    }
+
+    pub fn get_houses(&self) -> Vec<House> {
+        self.houses.clone()
+    }
+
+    pub fn get_candidate_weights(
+        &self,
+        house: String,
+        page: u64,
+        limit: u64,
+    ) -> Vec<(AccountId, u64)> {
+        let weights = self.house_weights(&house);
+        let start = std::cmp::min(page * limit, weights.len());
+        let end = std::cmp::min(start + limit, weights.len());
 
-        self.candidate_weights
+        weights
             .iter()
             .skip(start as usize)
             .take((end - start) as usize)
             .collect()
     }
 
-    pub fn get_total_candidates(&self) -> u64 {
-        self.candidate_weights.len()
+    pub fn get_total_candidates(&self, house: String) -> u64 {
+        self.house_weights(&house).len()
+    }
+
+    /// Returns the candidates that took the seats of the house.
+    ///
+    /// Candidates are ordered by weight, ties are broken by account ID in ascending order.
+    pub fn get_winners(&self, house: String) -> Vec<(AccountId, u64)> {
+        let seats = self
+            .houses
+            .iter()
+            .find(|h| h.name == house)
+            .map(|h| h.seats)
+            .unwrap_or_else(|| panic_str(UNKNOWN_HOUSE));
+
+        let mut results = self.house_weights(&house).to_vec();
+        results.sort_by(|(a, a_weight), (b, b_weight)| b_weight.cmp(a_weight).then(a.cmp(b)));
+        results.truncate(seats as usize);
+        results
+    }
+
+    pub fn get_admin(&self) -> AccountId {
+        self.admin.clone()
+    }
+
+    /// Returns the account proposed as the new admin
+    pub fn get_pending_admin(&self) -> Option<AccountId> {
+        self.pending_admin.clone()
     }
 
     pub fn get_relayer(&self) -> AccountId {
         self.relayer.clone()
     }
 
+    pub fn get_guardian(&self) -> AccountId {
+        self.guardian.clone()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Returns the time in milliseconds when the contract was paused
+    pub fn get_paused_at(&self) -> Option<Timestamp> {
+        self.paused_at
+    }
+
     pub fn get_end_time(&self) -> Timestamp {
         self.end_time_in_ms
     }
+
+    pub fn get_results_config(&self) -> ResultsConfig {
+        *self.results_config
+    }
+
+    /// Returns the end of the challenge of the results submitted by the relayer
+    pub fn get_results_challenge_end(&self) -> Option<Timestamp> {
+        self.results_challenge_end
+    }
+
+    pub fn is_results_finalized(&self) -> bool {
+        *self.status == VotingStatus::Finalized
+    }
+
+    pub fn get_status(&self) -> VotingStatus {
+        *self.status
+    }
+
+    pub fn get_disputes(&self, page: u64, limit: u64) -> Vec<Dispute> {
+        let start = page * limit;
+        let end = std::cmp::min(start + limit, self.disputes.len());
+
+        (start..end).filter_map(|i| self.disputes.get(i)).collect()
+    }
+
+    /// Returns the weights computed by the on-chain tally
+    pub fn get_tally_weights(&self, house: String, page: u64, limit: u64) -> Vec<(AccountId, u64)> {
+        let weights = self.house_tally(&house);
+        let start = std::cmp::min(page * limit, weights.len());
+        let end = std::cmp::min(start + limit, weights.len());
+
+        weights
+            .iter()
+            .skip(start as usize)
+            .take((end - start) as usize)
+            .collect()
+    }
+
+    /// Returns if all votes are processed by the on-chain tally
+    pub fn is_tally_completed(&self) -> bool {
+        !self.is_processing_batch() && self.processed_votes == self.votes.len()
+    }
+
+    pub fn get_snapshot_contract(&self) -> AccountId {
+        self.snapshot_contract.clone()
+    }
+
+    /// Returns bs58 compressed secp256k1 public key that encrypts the votes
+    pub fn get_encryption_public_key(&self) -> String {
+        bs58::encode(&self.encryption_public_key).into_string()
+    }
+
+    /// Returns if the final results are taken from the on-chain tally instead of the relayer results
+    pub fn is_onchain_tally(&self) -> bool {
+        self.onchain_tally
+    }
+
+    /// Returns the number of votes processed by the on-chain tally
+    pub fn get_processed_votes(&self) -> u64 {
+        self.processed_votes
+    }
 }
 
 #[cfg(test)]
 mod view_tests {
-    use near_sdk::{testing_env, NearToken};
+    use near_sdk::{env, testing_env, NearToken};
 
     use crate::{test_utils::*, types::EncryptedVoteView};
 
@@ -60,7 +190,8 @@ mod view_tests {
             })
             .collect::<Vec<_>>();
 
-        contract.send_encrypted_votes(votes_init.clone());
+        contract.send_encrypted_votes(votes_init[..100].to_vec());
+        contract.send_encrypted_votes(votes_init[100..].to_vec());
 
         assert_eq!(contract.get_total_votes(), 107);
 
@@ -77,29 +208,124 @@ mod view_tests {
     }
 
     #[test]
-    fn pagination_test_on_candidate_weights() {
+    fn vote_is_found_by_hash() {
         let (mut context, mut contract) = setup_ctr();
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(2), 10)]), ballot(2, vec![(acc(3), 5)])],
+        );
 
-        context.predecessor_account_id = relayer();
-        context.attached_deposit = NearToken::from_near(1);
-        context.block_timestamp = (end_time() + 1) * MSECOND;
-        testing_env!(context.clone());
+        let vote = contract.get_votes(1, 1).pop().unwrap();
+        let pubkey = bs58::decode(&vote.pubkey).into_vec().unwrap();
+        let hash = env::sha256_array(&[vote.vote.as_bytes(), &pubkey].concat()).into();
+        assert_eq!(contract.get_vote_index(hash), Some(1));
+        assert!(contract.has_vote(hash));
+
+        let unknown = env::sha256_array(vote.vote.as_bytes()).into();
+        assert_eq!(contract.get_vote_index(unknown), None);
+        assert!(!contract.has_vote(unknown));
+    }
+
+    #[test]
+    fn votes_commitment_chains_vote_hashes() {
+        let (mut context, mut contract) = setup_ctr();
+        assert_eq!(contract.get_votes_commitment(), [0; 32].into());
+
+        send_votes(
+            &mut context,
+            &mut contract,
+            &[ballot(1, vec![(acc(2), 10)]), ballot(2, vec![(acc(3), 5)])],
+        );
+
+        let commitment = contract
+            .get_votes(0, 10)
+            .iter()
+            .fold([0; 32], |commitment, vote| {
+                let pubkey = bs58::decode(&vote.pubkey).into_vec().unwrap();
+                let hash = env::sha256_array(&[vote.vote.as_bytes(), &pubkey].concat());
+                env::sha256_array(&[commitment, hash].concat())
+            });
+        assert_eq!(contract.get_votes_commitment(), commitment.into());
+    }
+
+    #[test]
+    fn pagination_test_on_candidate_weights() {
+        let (mut context, mut contract) = setup_ctr();
 
         let results_init = (0..107).map(|i| (acc(i), i as u64)).collect::<Vec<_>>();
 
-        contract.sumbit_results(results_init.clone());
+        submit_results(&mut context, &mut contract, results_init.clone());
 
-        assert_eq!(contract.get_total_candidates(), 107);
+        assert_eq!(contract.get_total_candidates(house()), 107);
 
-        let results = contract.get_candidate_weights(0, 10);
+        let results = contract.get_candidate_weights(house(), 0, 10);
         assert_eq!(results.len(), 10);
         assert_eq!(results, &results_init[0..10]);
 
-        let results = contract.get_candidate_weights(10, 10);
+        let results = contract.get_candidate_weights(house(), 10, 10);
         assert_eq!(results.len(), 7);
         assert_eq!(results, &results_init[100..]);
 
-        let results = contract.get_candidate_weights(55, 10);
+        let results = contract.get_candidate_weights(house(), 55, 10);
         assert_eq!(results.len(), 0);
     }
+
+    #[test]
+    fn winners_are_top_candidates_with_tie_break() {
+        let (mut context, mut contract) = setup_ctr();
+
+        submit_results(
+            &mut context,
+            &mut contract,
+            vec![
+                (acc(5), 10),
+                (acc(4), 30),
+                (acc(3), 20),
+                (acc(2), 20),
+                (acc(1), 5),
+            ],
+        );
+
+        assert_eq!(
+            contract.get_winners(house()),
+            vec![(acc(4), 30), (acc(2), 20), (acc(3), 20)]
+        );
+    }
+
+    #[test]
+    fn results_are_stored_per_house() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = relayer();
+        context.attached_deposit = NearToken::from_near(11);
+        context.block_timestamp = (end_time() + 1) * MSECOND;
+        testing_env!(context.clone());
+
+        let advisors = houses()[1].name.clone();
+        contract.sumbit_results(
+            vec![
+                (house(), vec![(acc(1), 1), (acc(2), 2)]),
+                (advisors.clone(), vec![(acc(3), 3), (acc(4), 4)]),
+            ],
+            contract.get_votes_commitment(),
+        );
+
+        assert_eq!(contract.get_houses(), houses());
+        assert_eq!(contract.get_total_candidates(house()), 2);
+        assert_eq!(contract.get_total_candidates(advisors.clone()), 2);
+        assert_eq!(contract.get_winners(advisors), vec![(acc(4), 4)]);
+        assert_eq!(
+            contract.get_winners(house()),
+            vec![(acc(2), 2), (acc(1), 1)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown house")]
+    fn cant_get_winners_of_unknown_house() {
+        let (_context, contract) = setup_ctr();
+
+        contract.get_winners("House of Lords".to_string());
+    }
 }
@@ -1,42 +1,115 @@
-use near_sdk::env;
+use near_sdk::{env, json_types::Base58CryptoHash};
 
-use crate::{consts::*, events::emit_phase_change, *};
-use common_contracts::finalize_storage_check;
+use crate::{
+    consts::*,
+    events::{
+        emit_admin_changed, emit_admin_proposed, emit_paused, emit_phase_change, emit_role_granted,
+        emit_role_revoked, emit_unpaused, emit_voters_loaded,
+    },
+    *,
+};
+use common_contracts::{
+    finalize_storage_check, refund_released_storage, refund_released_storage_to,
+    upgrade::deploy_and_migrate,
+};
 
 #[near_bindgen]
 impl Contract {
     /// *Transaction*: Sets the vote weight configuration
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - Only admin can set this
     /// - Can be set only during initialization phase
+    /// - The formula should be valid (e.g. non-zero root, sorted tiers starting from 0)
     pub fn set_vote_config(&mut self, vote_config: VoteWeightConfig) {
+        self.assert_not_paused();
         self.assert_initialization();
         self.assert_admin();
+        require!(vote_config.is_valid(), INVALID_VOTE_CONFIG);
 
-        self.vote_config = vote_config;
+        self.vote_config = vote_config.into();
     }
 
     /// *Transaction*: Bulk load voters
     ///
     /// Requirements:
-    /// - Only admin can bulk load voters
+    /// - The contract should not be paused
+    /// - Only admin or snapshot loader can bulk load voters
     /// - Can be done only during initialization phase
-    /// - The admin should pay for the extra storage
+    /// - The caller should pay for the extra storage
     #[payable]
     pub fn bulk_load_voters(&mut self, voters: Vec<(AccountId, UserData)>) {
         let current_storage_usage = env::storage_usage();
 
+        self.assert_not_paused();
         self.assert_initialization();
-        self.assert_admin();
+        self.assert_role(Role::SnapshotLoader);
 
         let mut new_accounts = 0;
         for (key, value) in voters.into_iter() {
-            if self.eligible_voters.insert(key, value).is_none() {
+            if self.eligible_voters.insert(key, value.into()).is_none() {
                 new_accounts += 1;
             }
         }
         self.total_eligible_users += new_accounts;
+        emit_voters_loaded(new_accounts, self.total_eligible_users);
+
+        self.eligible_voters.flush();
+        require!(
+            finalize_storage_check(current_storage_usage, 0),
+            STORAGE_LIMIT_EXCEEDED
+        );
+    }
+
+    /// *Transaction*: Removes voters from the snapshot
+    ///
+    /// Accounts that are not in the snapshot are skipped.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only admin or snapshot loader can remove voters
+    /// - Can be done only during initialization phase
+    /// - The released storage is refunded to the caller
+    pub fn bulk_remove_voters(&mut self, voters: Vec<AccountId>) {
+        let current_storage_usage = env::storage_usage();
+
+        self.assert_not_paused();
+        self.assert_initialization();
+        self.assert_role(Role::SnapshotLoader);
+
+        for account_id in voters.iter() {
+            if self.eligible_voters.remove(account_id).is_some() {
+                self.total_eligible_users -= 1;
+            }
+        }
+
+        self.eligible_voters.flush();
+        refund_released_storage(current_storage_usage);
+    }
+
+    /// *Transaction*: Updates the data of the voters in the snapshot
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only admin or snapshot loader can update voters
+    /// - Can be done only during initialization phase
+    /// - All voters should be in the snapshot. New voters should be bulk loaded instead.
+    /// - The caller should pay for the extra storage
+    #[payable]
+    pub fn bulk_update_voters(&mut self, voters: Vec<(AccountId, UserData)>) {
+        let current_storage_usage = env::storage_usage();
+
+        self.assert_not_paused();
+        self.assert_initialization();
+        self.assert_role(Role::SnapshotLoader);
+
+        for (account_id, user_data) in voters.into_iter() {
+            match self.eligible_voters.get_mut(&account_id) {
+                Some(data) => **data = user_data,
+                None => env::panic_str(NOT_ELIGIBLE_VOTER),
+            }
+        }
 
         self.eligible_voters.flush();
         require!(
@@ -45,67 +118,345 @@ impl Contract {
         );
     }
 
+    /// *Transaction*: Clears the snapshot of the previous attempt in batches
+    ///
+    /// Removes up to `limit` voters per call to fit into the gas limit.
+    /// The snapshot root and metadata of the previous attempt are reset by the call
+    /// that clears the last voter, so the new ones should be set after that.
+    /// Returns the number of voters left in the snapshot.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only admin or snapshot loader can clear the snapshot
+    /// - Can be done only during initialization phase
+    /// - The released storage is refunded to the admin
+    pub fn clear_snapshot(&mut self, limit: u32) -> u32 {
+        let current_storage_usage = env::storage_usage();
+
+        self.assert_not_paused();
+        self.assert_initialization();
+        self.assert_role(Role::SnapshotLoader);
+        require!(limit > 0, ZERO_LIMIT);
+
+        let batch: Vec<_> = self
+            .eligible_voters
+            .keys()
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        for account_id in batch.iter() {
+            self.eligible_voters.remove(account_id);
+        }
+        self.total_eligible_users -= batch.len() as u32;
+        if self.eligible_voters.is_empty() {
+            self.snapshot_root = None;
+            self.snapshot_metadata = None;
+        }
+
+        self.eligible_voters.flush();
+        refund_released_storage_to(current_storage_usage, self.admin.clone());
+
+        self.total_eligible_users
+    }
+
+    /// *Transaction*: Commits the Merkle root of the full snapshot
+    ///
+    /// Eligible users that were not bulk loaded can prove their data against it
+    /// during the registration. Pass `None` to remove the commitment.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only admin or snapshot loader can set this
+    /// - Can be set only during initialization phase
+    pub fn set_snapshot_root(&mut self, root: Option<Base58CryptoHash>) {
+        self.assert_not_paused();
+        self.assert_initialization();
+        self.assert_role(Role::SnapshotLoader);
+
+        self.snapshot_root = root.map(Into::into);
+    }
+
+    /// *Transaction*: Sets the provenance of the loaded snapshot
+    ///
+    /// Challengers verify the loaded data against the published snapshot.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only admin or snapshot loader can set this
+    /// - Can be set only during initialization phase
+    /// - IPFS CID should not be empty and fit the length limit
+    pub fn set_snapshot_metadata(&mut self, metadata: SnapshotMetadata) {
+        self.assert_not_paused();
+        self.assert_initialization();
+        self.assert_role(Role::SnapshotLoader);
+        require!(metadata.is_valid(), INVALID_SNAPSHOT_METADATA);
+
+        self.snapshot_metadata = Some(metadata);
+    }
+
+    /// *Transaction*: Sets the houses that nominees register into
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - Only admin can set this
+    /// - Can be set only during initialization phase
+    /// - At least one house, names should be unique and fit `MAX_HOUSE_NAME_LENGTH`,
+    ///   each house should have at least one seat
+    pub fn set_houses(&mut self, houses: Vec<House>) {
+        self.assert_not_paused();
+        self.assert_initialization();
+        self.assert_admin();
+        require!(House::are_valid(&houses), INVALID_HOUSES);
+
+        self.houses = houses;
+    }
+
     /// *Transaction*: Sets the snapshot configuration
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - Only admin can set this
     /// - Can be set only during initialization phase
     pub fn set_snapshot_config(&mut self, process_config: SnapshotConfig) {
+        self.assert_not_paused();
         self.assert_initialization();
         self.assert_admin();
 
-        self.process_config = process_config;
+        self.process_config = process_config.into();
     }
 
     /// *Transaction*: Starts the snapshot challenge phase once the snapshot is initialized
     ///
     /// Requirements:
-    /// - Only admin can start the challenge
+    /// - The contract should not be paused
+    /// - Only admin or phase operator can start the challenge
     /// - Can be started only during initialization phase
+    /// - Snapshot metadata should be set
+    /// - The caller should attach the bond from the snapshot config.
+    ///   The bond is returned to the admin once the snapshot passes the challenge.
+    #[payable]
     pub fn start_challenge(&mut self) {
+        self.assert_not_paused();
         self.assert_initialization();
-        self.assert_admin();
-        self.status = Status::SnapshotChallenge(self.status.attempt());
+        self.assert_role(Role::PhaseOperator);
+        require!(self.snapshot_metadata.is_some(), NO_SNAPSHOT_METADATA);
+        let admin_bond = env::attached_deposit();
+        require!(
+            admin_bond == NearToken::from_near(self.process_config.admin_bond_in_nears.into()),
+            INVALID_ADMIN_BOND
+        );
+        *self.status = Status::SnapshotChallenge(self.status.attempt());
         self.end_time_in_millis =
             env::block_timestamp_ms() + self.process_config.challenge_timeout_in_millis;
-        emit_phase_change(self.status);
+
+        let snapshot_root = self.snapshot_root.map(Into::into);
+        let total_eligible_users = self.total_eligible_users;
+        let metadata = self.snapshot_metadata.clone();
+        let record = self.current_attempt();
+        record.snapshot_root = snapshot_root;
+        record.metadata = metadata;
+        record.total_eligible_users = total_eligible_users;
+        record.admin_bond = admin_bond;
+        record.challenge_started_at = Some(env::block_timestamp_ms());
+        emit_phase_change(*self.status, self.snapshot_metadata.as_ref());
     }
 
     /// *Transaction*: Restarts the process to the initialization phase in case of snapshot issues
     ///
     /// Requirements:
-    /// - Only admin can restart the process
+    /// - The contract should not be paused
+    /// - Only admin or phase operator can restart the process
     /// - Can be restarted only during SnapshotChallenge or SnapshotHalted phase
     pub fn restart_to_initialization(&mut self) {
         // Admin can restart the process before the snapshot is halted
         // If some critical issues are found
         near_sdk::require!(
             matches!(
-                self.status,
+                *self.status,
                 Status::SnapshotChallenge(_) | Status::SnapshotHalted(_)
             ),
             RESTART_NOT_ALLOWED
         );
 
-        self.assert_admin();
-        self.status = Status::Initialization(self.status.attempt() + 1);
+        self.assert_not_paused();
+        self.assert_role(Role::PhaseOperator);
+        if matches!(*self.status, Status::SnapshotChallenge(_)) {
+            self.halt_attempt(HaltReason::AdminRestart);
+            // The bond goes to the challengers of the attempt if there are any
+            if self.current_attempt().total_challenged.is_zero() {
+                self.return_admin_bond();
+            }
+        }
+        *self.status = Status::Initialization(self.status.attempt() + 1);
+        self.attempts.push(AttemptRecord::new(
+            env::block_timestamp_ms(),
+            self.challenge_evidence.len(),
+        ));
         // We reset the total challenged to 0 so with the new iteration we can start from scratch
         // Though, we preserve the individual challenged amounts,
         // so user can return all the funds in the end
         self.total_challenged = NearToken::from_yoctonear(0);
-        emit_phase_change(self.status);
+        // The corrected snapshot should be published and described again
+        self.snapshot_metadata = None;
+        emit_phase_change(*self.status, None);
 
         // Now admin can bulk load data again and start the process
         // once issues are resolved
     }
 
+    /// *Transaction*: Proposes the new admin. The transfer is completed with `accept_admin`.
+    ///
+    /// Requirements:
+    /// - Only admin can propose the new admin
+    pub fn propose_admin(&mut self, account_id: AccountId) {
+        self.assert_admin();
+
+        emit_admin_proposed(&self.admin, &account_id);
+        self.pending_admin = Some(account_id);
+    }
+
+    /// *Transaction*: Accepts the admin role proposed with `propose_admin`
+    ///
+    /// Requirements:
+    /// - Only the proposed account can accept the role
+    pub fn accept_admin(&mut self) {
+        let caller = env::predecessor_account_id();
+        require!(
+            self.pending_admin.as_ref() == Some(&caller),
+            NOT_PENDING_ADMIN
+        );
+
+        emit_admin_changed(&self.admin, &caller);
+        self.admin = caller;
+        self.pending_admin = None;
+    }
+
+    /// *Transaction*: Grants the role to the account
+    ///
+    /// Requirements:
+    /// - Only admin can grant roles
+    /// - Owner role can't be granted, it's transferred with `propose_admin`
+    /// - The admin should pay for the extra storage
+    #[payable]
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        let current_storage_usage = env::storage_usage();
+
+        self.assert_admin();
+        require!(role != Role::Owner, OWNER_ROLE_GRANT);
+
+        let mut roles = self.roles.get(&account_id).cloned().unwrap_or_default();
+        if !roles.contains(&role) {
+            roles.push(role);
+            self.roles.insert(account_id.clone(), roles);
+            emit_role_granted(&account_id, role);
+        }
+
+        self.roles.flush();
+        require!(
+            finalize_storage_check(current_storage_usage, 0),
+            STORAGE_LIMIT_EXCEEDED
+        );
+    }
+
+    /// *Transaction*: Revokes the role from the account
+    ///
+    /// Requirements:
+    /// - Only admin can revoke roles
+    /// - The account should have the role
+    /// - The released storage is refunded to the admin
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        let current_storage_usage = env::storage_usage();
+
+        self.assert_admin();
+
+        let roles = self
+            .roles
+            .get_mut(&account_id)
+            .unwrap_or_else(|| env::panic_str(ROLE_NOT_GRANTED));
+        require!(roles.contains(&role), ROLE_NOT_GRANTED);
+        roles.retain(|granted| *granted != role);
+        if roles.is_empty() {
+            self.roles.remove(&account_id);
+        }
+        emit_role_revoked(&account_id, role);
+
+        self.roles.flush();
+        refund_released_storage(current_storage_usage);
+    }
+
+    /// *Transaction*: Pauses the state-changing methods of the contract in emergencies
+    ///
+    /// Role management, the admin transfer and the upgrade stay available,
+    /// so the issue can be fixed while the contract is paused.
+    ///
+    /// Requirements:
+    /// - Only admin or guardian can pause the contract
+    /// - The contract should not be paused
+    pub fn pause(&mut self) {
+        self.assert_role(Role::Guardian);
+        // The phase that is already over shouldn't be extended by the pause
+        self.try_move_stage();
+
+        self.paused_at = Some(env::block_timestamp_ms());
+        emit_paused(&env::predecessor_account_id());
+    }
+
+    /// *Transaction*: Unpauses the contract.
+    /// The deadline of the challenge or registration phase is shifted by the pause duration.
+    ///
+    /// Requirements:
+    /// - Only admin or guardian can unpause the contract
+    /// - The contract should be paused
+    pub fn unpause(&mut self) {
+        self.assert_role(Role::Guardian);
+        let paused_at = self
+            .paused_at
+            .take()
+            .unwrap_or_else(|| env::panic_str(NOT_PAUSED));
+
+        let paused_for = env::block_timestamp_ms().saturating_sub(paused_at);
+        if matches!(
+            *self.status,
+            Status::SnapshotChallenge(_) | Status::Registration(_)
+        ) {
+            self.end_time_in_millis += paused_for;
+        }
+        emit_unpaused(&env::predecessor_account_id(), paused_for);
+    }
+
+    /// *Transaction*: Deploys the new code of the contract and migrates the state
+    ///
+    /// The code is passed as the raw input of the call instead of JSON arguments.
+    /// The old code stays if the migration fails.
+    ///
+    /// Requirements:
+    /// - Only admin can upgrade the contract
+    pub fn upgrade(&self) -> Promise {
+        self.assert_admin();
+
+        deploy_and_migrate(MIGRATE_GAS)
+    }
+
+    /// Checks that the caller is the admin or has the given role
+    fn assert_role(&self, role: Role) {
+        let caller = env::predecessor_account_id();
+        near_sdk::require!(
+            caller == self.admin
+                || self
+                    .roles
+                    .get(&caller)
+                    .map_or(false, |roles| roles.contains(&role)),
+            NOT_AUTHORIZED
+        );
+    }
+
     fn assert_admin(&self) {
         near_sdk::require!(env::predecessor_account_id() == self.admin, NOT_AUTHORIZED);
     }
 
     fn assert_initialization(&self) {
         near_sdk::require!(
-            matches!(self.status, Status::Initialization(_)),
+            matches!(*self.status, Status::Initialization(_)),
             ON_INITIALIZATION_ONLY
         );
     }
@@ -113,11 +464,18 @@ impl Contract {
 
 #[cfg(test)]
 mod tests {
-    use near_sdk::{testing_env, NearToken};
+    use near_sdk::{
+        mock::MockAction,
+        test_utils::{get_created_receipts, get_logs},
+        testing_env, NearToken,
+    };
 
     use crate::{
         test_utils::*,
-        types::{SnapshotConfig, Status, UserData, VoteWeightConfig},
+        types::{
+            ActivityConfig, House, Role, SnapshotConfig, SnapshotMetadata, Status, UserData,
+            VoteWeightConfig,
+        },
     };
 
     #[test]
@@ -130,12 +488,16 @@ mod tests {
     #[test]
     fn admin_can_change_configs() {
         let (mut context, mut contract) = setup_ctr();
-        let new_vote_config = VoteWeightConfig {
-            threshold_in_nears: 200,
-            activity_reward_in_votes: 20,
+        let new_vote_config = VoteWeightConfig::Capped {
+            cap_in_nears: 200,
+            activity: ActivityConfig {
+                activity_reward_in_votes: 20,
+                max_active_months: Some(12),
+            },
         };
         let new_snapshot_config = SnapshotConfig {
             challenge_threshold_in_nears: 200,
+            admin_bond_in_nears: 20,
             challenge_timeout_in_millis: 200,
             registration_timeout_in_millis: 200,
         };
@@ -146,7 +508,7 @@ mod tests {
         context.predecessor_account_id = admin();
         testing_env!(context.clone());
 
-        contract.set_vote_config(new_vote_config);
+        contract.set_vote_config(new_vote_config.clone());
         contract.set_snapshot_config(new_snapshot_config);
 
         assert_eq!(contract.get_vote_config(), new_vote_config);
@@ -157,9 +519,8 @@ mod tests {
     #[should_panic(expected = "Not authorized")]
     fn non_admin_cannot_change_vote_config() {
         let (mut context, mut contract) = setup_ctr();
-        let new_vote_config = VoteWeightConfig {
-            threshold_in_nears: 200,
-            activity_reward_in_votes: 20,
+        let new_vote_config = VoteWeightConfig::Linear {
+            activity: default_activity_config(),
         };
 
         context.predecessor_account_id = acc(0);
@@ -174,6 +535,7 @@ mod tests {
         let (mut context, mut contract) = setup_ctr();
         let new_snapshot_config = SnapshotConfig {
             challenge_threshold_in_nears: 200,
+            admin_bond_in_nears: 20,
             challenge_timeout_in_millis: 200,
             registration_timeout_in_millis: 200,
         };
@@ -220,6 +582,129 @@ mod tests {
         assert_eq!(contract.get_total_eligible_users(), 4);
     }
 
+    #[test]
+    fn admin_can_remove_voters() {
+        let (mut context, mut contract) = setup_ctr();
+        let voters = load_voters();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(voters.clone());
+
+        context.attached_deposit = NearToken::from_yoctonear(0);
+        testing_env!(context.clone());
+        contract.bulk_remove_voters(vec![voters[0].0.clone(), acc(100)]);
+
+        assert!(!contract.is_eligible_voter(&voters[0].0));
+        assert!(contract.is_eligible_voter(&voters[1].0));
+        assert_eq!(contract.get_total_eligible_users(), 3);
+
+        let receipts = get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == admin()
+            && receipt.actions.iter().any(|action| matches!(
+                action,
+                MockAction::Transfer { deposit, .. } if deposit.as_yoctonear() > 0
+            ))));
+    }
+
+    #[test]
+    fn admin_can_update_voters() {
+        let (mut context, mut contract) = setup_ctr();
+        let voters = load_voters();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(voters.clone());
+
+        let updated = UserData::new(10, NearToken::from_near(100));
+        contract.bulk_update_voters(vec![(voters[0].0.clone(), updated.clone())]);
+
+        assert_eq!(
+            contract.get_eligible_voter_info(&voters[0].0),
+            Some(updated)
+        );
+        assert_eq!(contract.get_total_eligible_users(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not eligible voter")]
+    fn admin_cannot_update_unknown_voter() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.bulk_update_voters(vec![(acc(100), UserData::new(1, NearToken::from_near(1)))]);
+    }
+
+    #[test]
+    fn admin_can_clear_snapshot_after_restart() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(load_voters());
+
+        move_to_challenge(&mut context, &mut contract);
+        contract.restart_to_initialization();
+
+        context.attached_deposit = NearToken::from_yoctonear(0);
+        testing_env!(context.clone());
+        assert_eq!(contract.clear_snapshot(3), 1);
+        assert_eq!(contract.clear_snapshot(3), 0);
+
+        assert_eq!(contract.get_total_eligible_users(), 0);
+        assert!(load_voters()
+            .iter()
+            .all(|(account_id, _)| !contract.is_eligible_voter(account_id)));
+    }
+
+    #[test]
+    fn clearing_snapshot_refunds_admin_and_resets_commitments() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::SnapshotLoader);
+        contract.bulk_load_voters(load_voters());
+        contract.set_snapshot_root(Some([1; 32].into()));
+        contract.set_snapshot_metadata(metadata());
+
+        context.predecessor_account_id = acc(9);
+        context.attached_deposit = NearToken::from_yoctonear(0);
+        testing_env!(context.clone());
+        assert_eq!(contract.clear_snapshot(3), 1);
+
+        // The commitments are kept until the last voter is cleared
+        assert!(contract.get_snapshot_root().is_some());
+        assert_eq!(contract.get_snapshot_metadata(), Some(metadata()));
+
+        assert_eq!(contract.clear_snapshot(3), 0);
+
+        assert_eq!(contract.get_snapshot_root(), None);
+        assert_eq!(contract.get_snapshot_metadata(), None);
+        let receipts = get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == admin()
+            && receipt.actions.iter().any(|action| matches!(
+                action,
+                MockAction::Transfer { deposit, .. } if deposit.as_yoctonear() > 0
+            ))));
+        assert!(receipts.iter().all(|receipt| receipt.receiver_id != acc(9)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Allowed only during initialization phase")]
+    fn admin_cannot_clear_snapshot_after_initialization() {
+        let (mut context, mut contract) = setup_ctr();
+        move_to_challenge(&mut context, &mut contract);
+
+        contract.clear_snapshot(10);
+    }
+
     #[test]
     #[should_panic(expected = "Allowed only during initialization phase")]
     fn non_admin_cannot_bulk_load_voters_after_initialization() {
@@ -236,6 +721,7 @@ mod tests {
         let (mut context, mut contract) = setup_ctr();
         let new_snapshot_config = SnapshotConfig {
             challenge_threshold_in_nears: 200,
+            admin_bond_in_nears: 20,
             challenge_timeout_in_millis: 200,
             registration_timeout_in_millis: 200,
         };
@@ -257,6 +743,41 @@ mod tests {
         assert!(matches!(contract.get_status(), Status::Initialization(1)));
     }
 
+    #[test]
+    fn snapshot_metadata_is_recorded_and_reset_on_restart() {
+        let (mut context, mut contract) = setup_ctr();
+        assert_eq!(contract.get_snapshot_metadata(), Some(metadata()));
+
+        move_to_challenge(&mut context, &mut contract);
+        contract.restart_to_initialization();
+
+        assert_eq!(contract.get_attempt(0).unwrap().metadata, Some(metadata()));
+        assert_eq!(contract.get_snapshot_metadata(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Snapshot metadata is not set")]
+    fn admin_cannot_start_challenge_without_metadata() {
+        let (mut context, mut contract) = setup_ctr();
+        move_to_challenge(&mut context, &mut contract);
+        contract.restart_to_initialization();
+
+        contract.start_challenge();
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid snapshot metadata")]
+    fn admin_cannot_set_metadata_without_cid() {
+        let (mut context, mut contract) = setup_ctr();
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.set_snapshot_metadata(SnapshotMetadata {
+            ipfs_cid: String::new(),
+            ..metadata()
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Restart is not allowed")]
     fn admin_cannot_restart_approved_snapshot() {
@@ -269,4 +790,400 @@ mod tests {
 
         contract.restart_to_initialization();
     }
+
+    #[test]
+    fn admin_can_commit_snapshot_root() {
+        let (mut context, mut contract) = setup_ctr();
+        let (root, _) = merkle_tree(&load_voters());
+
+        assert_eq!(contract.get_snapshot_root(), None);
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.set_snapshot_root(Some(root.into()));
+        assert_eq!(contract.get_snapshot_root(), Some(root.into()));
+
+        contract.set_snapshot_root(None);
+        assert_eq!(contract.get_snapshot_root(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not authorized")]
+    fn non_admin_cannot_commit_snapshot_root() {
+        let (mut context, mut contract) = setup_ctr();
+        let (root, _) = merkle_tree(&load_voters());
+
+        context.predecessor_account_id = acc(0);
+        testing_env!(context.clone());
+
+        contract.set_snapshot_root(Some(root.into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Allowed only during initialization phase")]
+    fn admin_cannot_commit_snapshot_root_after_initialization() {
+        let (mut context, mut contract) = setup_ctr();
+        let (root, _) = merkle_tree(&load_voters());
+        move_to_challenge(&mut context, &mut contract);
+
+        contract.set_snapshot_root(Some(root.into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid vote weight config")]
+    fn admin_cannot_set_invalid_vote_config() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.set_vote_config(VoteWeightConfig::PiecewiseLinear {
+            tiers: vec![],
+            activity: default_activity_config(),
+        });
+    }
+
+    #[test]
+    fn admin_can_set_houses() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        let houses = vec![House {
+            name: "Transparency Commission".to_string(),
+            seats: 7,
+        }];
+        contract.set_houses(houses.clone());
+
+        assert_eq!(contract.get_houses(), houses);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid houses configuration")]
+    fn admin_cannot_set_duplicated_houses() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        let mut houses = houses();
+        houses.push(houses[0].clone());
+        contract.set_houses(houses);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid houses configuration")]
+    fn admin_cannot_set_house_without_seats() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.set_houses(vec![House {
+            name: "House of Merit".to_string(),
+            seats: 0,
+        }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid houses configuration")]
+    fn admin_cannot_set_empty_houses() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+
+        contract.set_houses(vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Allowed only during initialization phase")]
+    fn admin_cannot_set_houses_after_initialization() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+
+        contract.set_houses(houses());
+    }
+
+    #[test]
+    fn admin_is_transferred_in_two_steps() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.propose_admin(acc(9));
+
+        // The admin keeps the rights until the new one accepts
+        assert_eq!(contract.get_admin(), admin());
+        assert_eq!(contract.get_pending_admin(), Some(acc(9)));
+
+        context.predecessor_account_id = acc(9);
+        testing_env!(context.clone());
+        contract.accept_admin();
+
+        assert_eq!(contract.get_admin(), acc(9));
+        assert_eq!(contract.get_pending_admin(), None);
+        assert_eq!(contract.get_roles(&acc(9)), vec![Role::Owner]);
+        assert!(contract.get_roles(&admin()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed admin can accept the role")]
+    fn only_proposed_account_can_accept_admin() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.propose_admin(acc(9));
+
+        context.predecessor_account_id = acc(8);
+        testing_env!(context.clone());
+        contract.accept_admin();
+    }
+
+    #[test]
+    fn snapshot_loader_can_load_voters() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::SnapshotLoader);
+
+        assert_eq!(contract.get_roles(&acc(9)), vec![Role::SnapshotLoader]);
+        assert_eq!(
+            contract.get_role_members(0, 10),
+            vec![(acc(9), vec![Role::SnapshotLoader])]
+        );
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"role_granted","data":{"account_id":"user-9.near","role":"SnapshotLoader"}}"#
+            ]
+        );
+
+        context.predecessor_account_id = acc(9);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(load_voters());
+
+        assert_eq!(contract.get_total_eligible_users(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not authorized")]
+    fn snapshot_loader_cannot_start_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::SnapshotLoader);
+
+        context.predecessor_account_id = acc(9);
+        context.attached_deposit =
+            NearToken::from_near(contract.get_process_config().admin_bond_in_nears.into());
+        testing_env!(context.clone());
+        contract.start_challenge();
+    }
+
+    #[test]
+    #[should_panic(expected = "Not authorized")]
+    fn revoked_snapshot_loader_cannot_load_voters() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::SnapshotLoader);
+        contract.revoke_role(acc(9), Role::SnapshotLoader);
+        assert!(contract.get_roles(&acc(9)).is_empty());
+        assert!(contract.get_role_members(0, 10).is_empty());
+
+        context.predecessor_account_id = acc(9);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(load_voters());
+    }
+
+    #[test]
+    fn phase_operator_can_start_challenge_and_restart() {
+        let (mut context, mut contract) = setup_ctr();
+        let bond = NearToken::from_near(contract.get_process_config().admin_bond_in_nears.into());
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::PhaseOperator);
+
+        context.predecessor_account_id = acc(9);
+        context.attached_deposit = bond;
+        testing_env!(context.clone());
+        contract.start_challenge();
+
+        assert!(matches!(
+            contract.get_status(),
+            Status::SnapshotChallenge(0)
+        ));
+        assert_eq!(contract.get_attempt(0).unwrap().admin_bond, bond);
+
+        contract.restart_to_initialization();
+
+        assert!(matches!(contract.get_status(), Status::Initialization(1)));
+        // Nobody challenged, so the bond is returned to the admin
+        assert!(get_created_receipts()
+            .iter()
+            .any(|receipt| receipt.receiver_id == admin()
+                && receipt.actions.iter().any(|action| matches!(
+                    action,
+                    MockAction::Transfer { deposit, .. } if *deposit == bond
+                ))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner role can be transferred only with propose_admin")]
+    fn admin_cannot_grant_owner_role() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::Owner);
+    }
+
+    #[test]
+    fn guardian_can_pause_and_unpause_with_deadline_shift() {
+        let (mut context, mut contract) = setup_ctr();
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+        let end_time = contract.get_end_time();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.grant_role(acc(9), Role::Guardian);
+
+        context.predecessor_account_id = acc(9);
+        testing_env!(context.clone());
+        contract.pause();
+
+        let paused_at = context.block_timestamp / MSECOND;
+        assert!(contract.is_paused());
+        assert_eq!(contract.get_paused_at(), Some(paused_at));
+
+        context.block_timestamp += 100 * MSECOND;
+        testing_env!(context.clone());
+        contract.unpause();
+
+        assert!(!contract.is_paused());
+        assert_eq!(contract.get_paused_at(), None);
+        assert_eq!(contract.get_end_time(), end_time + 100);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"unpaused","data":{"by":"user-9.near","paused_for_ms":100}}"#
+            ]
+        );
+
+        // The registration lasts for the pause duration longer
+        context.block_timestamp = (end_time + 1) * MSECOND;
+        testing_env!(context.clone());
+        contract.try_move_stage();
+        assert!(matches!(contract.get_status(), Status::Registration(_)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn paused_contract_rejects_registration() {
+        let (mut context, mut contract) = setup_ctr();
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.pause();
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"paused","data":{"by":"admin.near"}}"#
+            ]
+        );
+
+        context.predecessor_account_id = acc(1);
+        context.signer_account_id = acc(1);
+        context.attached_deposit = NearToken::from_millinear(5);
+        testing_env!(context.clone());
+        contract.register_as_voter(None);
+    }
+
+    #[test]
+    fn pause_does_not_extend_finished_phase() {
+        let (mut context, mut contract) = setup_ctr();
+        move_to_challenge(&mut context, &mut contract);
+        let end_time = contract.get_end_time();
+
+        // The challenge is over, but nobody moved the stage yet
+        context.predecessor_account_id = admin();
+        context.block_timestamp = (end_time + 1) * MSECOND;
+        testing_env!(context.clone());
+        contract.pause();
+
+        assert!(matches!(contract.get_status(), Status::Registration(_)));
+        let registration_end = contract.get_end_time();
+
+        context.block_timestamp += 100 * MSECOND;
+        testing_env!(context.clone());
+        contract.unpause();
+
+        assert_eq!(contract.get_end_time(), registration_end + 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not authorized")]
+    fn non_guardian_cannot_pause() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = acc(0);
+        testing_env!(context.clone());
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is not paused")]
+    fn cannot_unpause_active_contract() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.unpause();
+    }
+
+    #[test]
+    fn admin_can_upgrade_contract() {
+        let (mut context, contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.input = vec![1, 2, 3];
+        testing_env!(context.clone());
+        contract.upgrade();
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, context.current_account_id);
+        assert!(matches!(
+            &receipts[0].actions[..],
+            [
+                MockAction::DeployContract { code, .. },
+                MockAction::FunctionCallWeight { method_name, .. },
+            ] if code == &vec![1, 2, 3] && method_name == b"migrate"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not authorized")]
+    fn non_admin_cannot_upgrade_contract() {
+        let (mut context, contract) = setup_ctr();
+
+        context.predecessor_account_id = acc(0);
+        context.input = vec![1, 2, 3];
+        testing_env!(context.clone());
+        contract.upgrade();
+    }
 }
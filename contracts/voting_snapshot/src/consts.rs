@@ -1,9 +1,16 @@
 use near_sdk::{Gas, StorageUsage};
 
 pub const NOT_AUTHORIZED: &str = "Not authorized";
+pub const PAUSED: &str = "Contract is paused";
+pub const NOT_PAUSED: &str = "Contract is not paused";
+pub const NOT_PENDING_ADMIN: &str = "Only the proposed admin can accept the role";
+pub const OWNER_ROLE_GRANT: &str = "Owner role can be transferred only with propose_admin";
+pub const ROLE_NOT_GRANTED: &str = "Role is not granted to the account";
 pub const NOT_ELIGIBLE_VOTER: &str = "Not eligible voter";
+pub const ZERO_LIMIT: &str = "Limit should be greater than zero";
 pub const DIRECT_CALL: &str = "Should be called directly";
 pub const CHALLENGE_OVERFLOW: &str = "Overflow on total challenged";
+pub const INVALID_VOTE_CONFIG: &str = "Invalid vote weight config";
 
 pub const ON_INITIALIZATION_ONLY: &str = "Allowed only during initialization phase";
 pub const ON_REGISTRATION_ONLY: &str = "Allowed only during registration phase";
@@ -14,18 +21,45 @@ pub const RESTART_NOT_ALLOWED: &str = "Restart is not allowed";
 
 pub const ALREADY_REGISTERED: &str = "Already registered";
 pub const NOT_REGISTERED: &str = "Not registered";
+pub const NOT_NOMINEE: &str = "Not a nominee";
+pub const INVALID_NOMINEE_PROFILE: &str = "Invalid nominee profile";
+pub const UNKNOWN_HOUSE: &str = "Unknown house";
+pub const INVALID_HOUSES: &str = "Invalid houses configuration";
+pub const INVALID_CHALLENGE_EVIDENCE: &str = "Invalid challenge evidence";
+pub const NOT_CHALLENGER: &str = "Only challengers of the current attempt can submit evidence";
+pub const REFUND_PENDING: &str = "Refund is pending for the user";
 pub const NO_DEPOSIT: &str = "No deposit found for the user";
+pub const INVALID_ADMIN_BOND: &str = "Attached deposit should be equal to the admin bond";
 pub const EXPECTED_DEPOSIT: &str = "Expected deposit greater than 1 milli NEAR";
 pub const EXPECTED_PROMISE_RESULT: &str = "Expected 1 promise result";
+pub const INVALID_SNAPSHOT_METADATA: &str = "Invalid snapshot metadata";
+pub const NO_SNAPSHOT_METADATA: &str = "Snapshot metadata is not set";
+pub const NO_SNAPSHOT_ROOT: &str = "Snapshot root is not set";
+pub const INVALID_SNAPSHOT_PROOF: &str = "Invalid snapshot proof";
+
+pub const NO_STATE: &str = "No state to migrate";
+pub const UNKNOWN_STATE_VERSION: &str = "Unknown state version";
 
 pub const STORAGE_LIMIT_EXCEEDED: &str = "Deposit is not enough to cover storage usage";
 
+pub const MAX_PLATFORM_LENGTH: usize = 4096;
+pub const MAX_LINKS: usize = 10;
+
 // The eligible voter that want to participate in the voting.
 // Should also cover the storage for the snapshot voter information.
+// The account ID is stored twice, as the key of the record and in the key index of the map.
 // It's not precise (as it depends on AccountId length), but should be enough for the estimation.
-pub const SNAPSHOT_RECORD_COST: StorageUsage = 90;
+pub const SNAPSHOT_RECORD_COST: StorageUsage = 180;
 
 // Testnet execution shows 3.14 TGas for this function
 // As a safety measure, we will use 5 TGas
 // https://testnet.nearblocks.io/txns/BDURcv5JibwkYVxy53bQQ2eGqNMRpLnoBwyqC8a4aet8#execution
 pub const ON_REFUND_SUCCESS_GAS: Gas = Gas::from_tgas(5);
+
+// Version of the state layout. Bumped with each release that changes the layout.
+pub const STATE_VERSION: u32 = 1;
+// Gas for the migration of the state after the upgrade
+pub const MIGRATE_GAS: Gas = Gas::from_tgas(50);
+
+// Gas reserved for each refund in `refund_all`: the callback and scheduling of the promises
+pub const REFUND_BATCH_ITEM_GAS: Gas = Gas::from_tgas(10);
@@ -1,24 +1,126 @@
-use near_sdk::{serde::Serialize, serde_json::json};
+use common_contracts::events::snapshot::SnapshotEvent;
+use common_contracts::events::StandardEvent;
 
-use common_contracts::events::{EventPayload, NearEvent};
+use near_sdk::{AccountId, NearToken, PublicKey};
 
-use crate::types::Status;
+use crate::types::{ChallengeEvidence, Role, SnapshotMetadata, Status, VoteWeight};
 
-fn emit_event<T: Serialize>(event: EventPayload<T>) {
-    NearEvent {
-        standard: "ndc-snapshot",
-        version: "1.0.0",
-        event,
+pub fn emit_phase_change(phase: Status, snapshot: Option<&SnapshotMetadata>) {
+    let (attempt, phase) = phase.event_info();
+    SnapshotEvent::PhaseChange {
+        attempt,
+        phase,
+        snapshot: snapshot.cloned(),
     }
     .emit();
 }
 
-pub fn emit_phase_change(phase: Status) {
-    let (attempt, phase_name) = phase.event_info();
-    emit_event(EventPayload {
-        event: "phase_change",
-        data: json!({ "phase": phase_name, "attempt": attempt}),
-    });
+pub fn emit_voters_loaded(count: u32, total_eligible_users: u32) {
+    SnapshotEvent::VotersLoaded {
+        count,
+        total_eligible_users,
+    }
+    .emit();
+}
+
+pub fn emit_voter_registered(account_id: &AccountId, vote_weight: VoteWeight) {
+    SnapshotEvent::VoterRegistered {
+        account_id: account_id.clone(),
+        vote_weight,
+    }
+    .emit();
+}
+
+pub fn emit_public_key_changed(account_id: &AccountId, public_key: &PublicKey) {
+    SnapshotEvent::PublicKeyChanged {
+        account_id: account_id.clone(),
+        public_key: public_key.clone(),
+    }
+    .emit();
+}
+
+pub fn emit_nominee_registered(account_id: &AccountId, house: &str) {
+    SnapshotEvent::NomineeRegistered {
+        account_id: account_id.clone(),
+        house: house.to_string(),
+    }
+    .emit();
+}
+
+pub fn emit_snapshot_challenged(
+    attempt: u32,
+    challenger: &AccountId,
+    amount: NearToken,
+    total_challenged: NearToken,
+) {
+    SnapshotEvent::SnapshotChallenged {
+        amount,
+        attempt,
+        challenger: challenger.clone(),
+        total_challenged,
+    }
+    .emit();
+}
+
+pub fn emit_bond_refunded(account_id: &AccountId, amount: NearToken) {
+    SnapshotEvent::BondRefunded {
+        account_id: account_id.clone(),
+        amount,
+    }
+    .emit();
+}
+
+pub fn emit_challenge_evidence(attempt: u32, challenger: &AccountId, evidence: &ChallengeEvidence) {
+    SnapshotEvent::ChallengeEvidence {
+        attempt,
+        challenger: challenger.clone(),
+        evidence: evidence.clone(),
+    }
+    .emit();
+}
+
+pub fn emit_admin_proposed(admin: &AccountId, proposed: &AccountId) {
+    SnapshotEvent::AdminProposed {
+        admin: admin.clone(),
+        proposed: proposed.clone(),
+    }
+    .emit();
+}
+
+pub fn emit_admin_changed(old_admin: &AccountId, new_admin: &AccountId) {
+    SnapshotEvent::AdminChanged {
+        new_admin: new_admin.clone(),
+        old_admin: old_admin.clone(),
+    }
+    .emit();
+}
+
+pub fn emit_role_granted(account_id: &AccountId, role: Role) {
+    SnapshotEvent::RoleGranted {
+        account_id: account_id.clone(),
+        role,
+    }
+    .emit();
+}
+
+pub fn emit_role_revoked(account_id: &AccountId, role: Role) {
+    SnapshotEvent::RoleRevoked {
+        account_id: account_id.clone(),
+        role,
+    }
+    .emit();
+}
+
+pub fn emit_paused(by: &AccountId) {
+    SnapshotEvent::Paused { by: by.clone() }.emit();
+}
+
+pub fn emit_unpaused(by: &AccountId, paused_for_ms: u64) {
+    SnapshotEvent::Unpaused {
+        by: by.clone(),
+        paused_for_ms,
+    }
+    .emit();
 }
 
 #[cfg(test)]
@@ -29,8 +131,83 @@ mod unit_tests {
 
     #[test]
     fn log_vote() {
-        let expected1 = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.0.0","event":"phase_change","data":{"attempt":3,"phase":"RegistrationEnded"}}"#;
-        emit_phase_change(Status::RegistrationEnded(3));
+        let expected1 = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"phase_change","data":{"attempt":3,"phase":"RegistrationEnded","snapshot":null}}"#;
+        emit_phase_change(Status::RegistrationEnded(3), None);
         assert_eq!(vec![expected1], test_utils::get_logs());
     }
+
+    #[test]
+    fn log_phase_change_with_snapshot() {
+        let metadata = SnapshotMetadata {
+            block_height: 108194270,
+            ipfs_cid: "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string(),
+            data_hash: [1; 32].into(),
+            formula_version: 1,
+        };
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"phase_change","data":{"attempt":0,"phase":"SnapshotChallenge","snapshot":{"block_height":108194270,"data_hash":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","formula_version":1,"ipfs_cid":"bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"}}}"#;
+        emit_phase_change(Status::SnapshotChallenge(0), Some(&metadata));
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_role_changes() {
+        let account_id: AccountId = "loader.near".parse().unwrap();
+        let expected = vec![
+            r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"role_granted","data":{"account_id":"loader.near","role":"SnapshotLoader"}}"#,
+            r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"role_revoked","data":{"account_id":"loader.near","role":"SnapshotLoader"}}"#,
+        ];
+        emit_role_granted(&account_id, Role::SnapshotLoader);
+        emit_role_revoked(&account_id, Role::SnapshotLoader);
+        assert_eq!(expected, test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_voters_loaded() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"voters_loaded","data":{"count":2,"total_eligible_users":4}}"#;
+        emit_voters_loaded(2, 4);
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_voter_registered() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"voter_registered","data":{"account_id":"alice.near","vote_weight":1020}}"#;
+        emit_voter_registered(&"alice.near".parse().unwrap(), 1020);
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_public_key_changed() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"public_key_changed","data":{"account_id":"alice.near","public_key":"ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"}}"#;
+        let public_key = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap();
+        emit_public_key_changed(&"alice.near".parse().unwrap(), &public_key);
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_nominee_registered() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"nominee_registered","data":{"account_id":"alice.near","house":"House of Merit"}}"#;
+        emit_nominee_registered(&"alice.near".parse().unwrap(), "House of Merit");
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_snapshot_challenged() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"snapshot_challenged","data":{"amount":"1000000000000000000000000","attempt":1,"challenger":"alice.near","total_challenged":"5000000000000000000000000"}}"#;
+        emit_snapshot_challenged(
+            1,
+            &"alice.near".parse().unwrap(),
+            NearToken::from_near(1),
+            NearToken::from_near(5),
+        );
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
+
+    #[test]
+    fn log_bond_refunded() {
+        let expected = r#"EVENT_JSON:{"standard":"ndc-snapshot","version":"1.1.0","event":"bond_refunded","data":{"account_id":"alice.near","amount":"1000000000000000000000000"}}"#;
+        emit_bond_refunded(&"alice.near".parse().unwrap(), NearToken::from_near(1));
+        assert_eq!(vec![expected], test_utils::get_logs());
+    }
 }
@@ -1,6 +1,11 @@
-use near_sdk::{ext_contract, AccountId};
+use near_sdk::{ext_contract, AccountId, NearToken};
 
 #[ext_contract(ext_self)]
 pub trait ExtSelf {
-    fn on_refund_success(&mut self, account_id: AccountId);
+    fn on_refund_success(
+        &mut self,
+        account_id: AccountId,
+        amount: NearToken,
+        rewarded_attempts: Vec<u32>,
+    );
 }
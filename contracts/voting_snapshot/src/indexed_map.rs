@@ -0,0 +1,144 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::store::{LookupMap, Vector};
+use near_sdk::{env, IntoStorageKey};
+
+/// Lookup map that can be enumerated in the insertion order.
+///
+/// The keys are indexed in a vector and the position of the key is stored next to the value,
+/// so insertion and removal are constant in gas and the map doesn't pay for the ordering.
+/// The removed key is replaced with the last one, so the order is not preserved on removal.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct IndexedMap<K, V>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    values: LookupMap<K, (u32, V)>,
+    keys: Vector<K>,
+}
+
+impl<K, V> IndexedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+{
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        let prefix = prefix.into_storage_key();
+        Self {
+            values: LookupMap::new([prefix.as_slice(), b"v"].concat()),
+            keys: Vector::new([prefix.as_slice(), b"k"].concat()),
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.values.get(key).map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.values.get_mut(key).map(|(_, value)| value)
+    }
+
+    /// Inserts the value and returns the previous one
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some((_, old)) = self.values.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        self.values.insert(key.clone(), (self.keys.len(), value));
+        self.keys.push(key);
+        None
+    }
+
+    /// Removes the value and moves the last key into the position of the removed one
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (index, value) = self.values.remove(key)?;
+        self.keys.swap_remove(index);
+        if let Some(moved) = self.keys.get(index) {
+            let (position, _) = self.values.get_mut(moved).unwrap_or_else(|| env::abort());
+            *position = index;
+        }
+        Some(value)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.keys.iter()
+    }
+
+    /// Returns up to `limit` entries starting from the given position in the key index.
+    /// The skipped entries are not read from the storage.
+    pub fn page(&self, from_index: u32, limit: u32) -> impl Iterator<Item = (&K, &V)> {
+        self.keys
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(move |key| (key, self.get(key).unwrap_or_else(|| env::abort())))
+    }
+
+    pub fn flush(&mut self) {
+        self.values.flush();
+        self.keys.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(keys: &[u8]) -> IndexedMap<u8, u32> {
+        let mut map = IndexedMap::new(b"m".to_vec());
+        for key in keys {
+            assert_eq!(map.insert(*key, *key as u32), None);
+        }
+        map
+    }
+
+    #[test]
+    fn keys_are_enumerated_in_insertion_order() {
+        let map = map(&[3, 1, 2]);
+
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 1, 2]);
+        assert_eq!(
+            map.page(1, 5).map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2)]
+        );
+        assert_eq!(map.page(3, 5).count(), 0);
+    }
+
+    #[test]
+    fn insert_replaces_value_without_reindexing() {
+        let mut map = map(&[1, 2]);
+
+        assert_eq!(map.insert(1, 10), Some(1));
+        assert_eq!(map.get(&1), Some(&10));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn removed_key_is_replaced_with_last_one() {
+        let mut map = map(&[1, 2, 3, 4]);
+
+        assert_eq!(map.remove(&2), Some(2));
+        assert_eq!(map.remove(&2), None);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 4, 3]);
+
+        // The moved key can still be removed from its new position
+        assert_eq!(map.remove(&4), Some(4));
+        assert_eq!(map.remove(&3), Some(3));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(map.remove(&1), Some(1));
+        assert!(map.is_empty());
+        assert!(!map.contains_key(&1));
+    }
+}
@@ -1,24 +1,41 @@
-use events::emit_phase_change;
+use events::{
+    emit_bond_refunded, emit_challenge_evidence, emit_nominee_registered, emit_phase_change,
+    emit_public_key_changed, emit_snapshot_challenged, emit_voter_registered,
+};
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::env::{predecessor_account_id, signer_account_id};
-use near_sdk::store::{LookupMap, LookupSet};
+use near_sdk::store::{LookupMap, LookupSet, Vector};
 use near_sdk::{
-    env, near_bindgen, require, AccountId, NearToken, PanicOnDefault, Promise, PromiseResult,
-    PublicKey,
+    env, near_bindgen, require, AccountId, CryptoHash, NearToken, PanicOnDefault, Promise,
+    PromiseResult, PublicKey, StorageUsage,
 };
 
 pub mod admin;
 pub mod consts;
 pub mod events;
 pub mod ext;
+pub mod indexed_map;
+pub mod merkle;
+pub mod migration;
 pub mod storage;
 pub mod types;
+pub mod versioned;
 pub mod view;
 
-use common_contracts::finalize_storage_check;
+use common_contracts::{
+    finalize_storage_check, refund_released_storage, upgrade::write_state_version,
+};
 use consts::*;
+use indexed_map::IndexedMap;
 use storage::StorageKey;
-use types::{SnapshotConfig, Status, UserData, VoteWeightConfig};
+use types::{
+    AttemptRecord, ChallengeEvidence, EvidenceRecord, HaltReason, House, NomineeProfile,
+    RefundState, Role, SnapshotConfig, SnapshotMetadata, SnapshotProof, Status, UserData,
+    VoteWeightConfig,
+};
+use versioned::{
+    VersionedSnapshotConfig, VersionedStatus, VersionedUserData, VersionedVoteWeightConfig,
+};
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 pub mod test_utils;
@@ -28,35 +45,69 @@ pub mod test_utils;
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct Contract {
-    vote_config: types::VoteWeightConfig,
-    process_config: types::SnapshotConfig,
+    vote_config: VersionedVoteWeightConfig,
+    process_config: VersionedSnapshotConfig,
+    // Owner of the contract. Transferred in two steps with `propose_admin` and `accept_admin`.
     admin: AccountId,
-    status: Status,
+    pending_admin: Option<AccountId>,
+    // Roles delegated by the admin to other accounts
+    roles: IndexedMap<AccountId, Vec<Role>>,
+    status: VersionedStatus,
 
     end_time_in_millis: u64,
+    // Set while the contract is paused. The deadline is shifted by the pause duration on unpause.
+    paused_at: Option<u64>,
 
     // This is basically a snapshot but without unnecessary data
     // for full snapshot, please refer to the IPFS storage
-    // Will be cleaned on halt.
-    eligible_voters: LookupMap<AccountId, UserData>,
+    // Can be cleared with `clear_snapshot` after the restart to initialization.
+    eligible_voters: IndexedMap<AccountId, VersionedUserData>,
     total_eligible_users: u32,
 
+    // Merkle root of the full snapshot. Users that were not bulk loaded
+    // can prove their snapshot data against it during the registration.
+    snapshot_root: Option<CryptoHash>,
+    // Provenance of the loaded snapshot. Should be set before the challenge.
+    snapshot_metadata: Option<SnapshotMetadata>,
+
     // We need to collect the ones who want to participate in the vote process
     // We collect the public key of the voter to verify the signature
     // in the encoded message.
     // Also, this user indicates that he/she accepts conduct of fair voting
     voters: LookupMap<AccountId, PublicKey>,
+    // Voters in the order of registration to enumerate them
+    voters_index: Vector<AccountId>,
     total_voters: u32,
 
-    nominees: LookupSet<AccountId>,
+    // Elected bodies that the nominees register into
+    houses: Vec<House>,
+
+    // Nominees with the profiles they share with the voters.
+    // Withdrawn nominees are removed, so they are not a part of the tally.
+    nominees: IndexedMap<AccountId, NomineeProfile>,
 
     // People can deposit NEAR to challenge snapshot
     // If the challenge is successful, the voting snapshot will be halted
     // Also, this provides amount of how much user spent to challenge the snapshot
     // The total number of challengers can be > than the config parameters because of the
     // several attempts.
-    challengers: LookupMap<AccountId, NearToken>,
+    // Refunded challengers are removed, so the remaining ones can be refunded with `refund_all`.
+    challengers: IndexedMap<AccountId, NearToken>,
     total_challenged: NearToken,
+
+    // History of the attempts, indexed by the attempt number
+    attempts: Vector<AttemptRecord>,
+    // Deposits of the challengers in each attempt.
+    // Minimal challenge deposit covers the storage of the entry.
+    attempt_challenges: LookupMap<(u32, AccountId), NearToken>,
+    // Evidence of all attempts in the order of submission.
+    // Evidence of the attempt is a continuous range described in the attempt record.
+    challenge_evidence: Vector<EvidenceRecord>,
+    // Attempts in which the challenger already claimed the share of the admin bond
+    claimed_rewards: LookupSet<(u32, AccountId)>,
+    // Refund state of each challenger. It's set before the refund transfer,
+    // so the same deposit can't be refunded twice while the callback is pending.
+    refund_states: LookupMap<AccountId, RefundState>,
 }
 
 // Implement the contract structure
@@ -69,34 +120,54 @@ impl Contract {
         vote_config: VoteWeightConfig,
         process_config: SnapshotConfig,
     ) -> Self {
+        require!(vote_config.is_valid(), INVALID_VOTE_CONFIG);
+
         let status = Status::Initialization(0);
-        emit_phase_change(status);
+        emit_phase_change(status, None);
+
+        let mut attempts = Vector::new(StorageKey::Attempts);
+        attempts.push(AttemptRecord::new(env::block_timestamp_ms(), 0));
+        write_state_version(STATE_VERSION);
+
         Self {
             admin,
-            status,
-            process_config,
-            vote_config,
+            pending_admin: None,
+            roles: IndexedMap::new(StorageKey::Roles),
+            status: status.into(),
+            process_config: process_config.into(),
+            vote_config: vote_config.into(),
             end_time_in_millis: 0,
+            paused_at: None,
             total_voters: 0,
             total_eligible_users: 0,
-            eligible_voters: LookupMap::new(StorageKey::EligibleVoters),
+            snapshot_root: None,
+            snapshot_metadata: None,
+            houses: Vec::new(),
+            eligible_voters: IndexedMap::new(StorageKey::EligibleVoters),
             voters: LookupMap::new(StorageKey::Voters),
-            nominees: LookupSet::new(StorageKey::Nominees),
-            challengers: LookupMap::new(StorageKey::Challengers),
+            voters_index: Vector::new(StorageKey::VotersIndex),
+            nominees: IndexedMap::new(StorageKey::Nominees),
+            challengers: IndexedMap::new(StorageKey::Challengers),
             total_challenged: NearToken::from_millinear(0),
+            attempts,
+            attempt_challenges: LookupMap::new(StorageKey::AttemptChallenges),
+            challenge_evidence: Vector::new(StorageKey::ChallengeEvidence),
+            claimed_rewards: LookupSet::new(StorageKey::ClaimedRewards),
+            refund_states: LookupMap::new(StorageKey::RefundStates),
         }
     }
 
     /// *Transaction*: Registers the user as a voter
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - The contract should be in the registration phase
-    /// - User should be eligible
+    /// - User should be eligible (bulk loaded or proven with the snapshot proof)
     /// - User should not be registered before
     /// - User should pay for storage including the snapshot record cost
     /// - User should call directly as we parse signer public key from the input
     #[payable]
-    pub fn register_as_voter(&mut self) {
+    pub fn register_as_voter(&mut self, proof: Option<SnapshotProof>) {
         let storage = env::storage_usage();
 
         let signer = signer_account_id();
@@ -105,14 +176,17 @@ impl Contract {
 
         self.try_move_stage();
 
-        self.assert_eligible_voter(&signer);
+        let record_cost = self.assert_eligible_voter(&signer, proof);
 
-        self.voters.insert(signer, env::signer_account_pk());
+        self.voters.insert(signer.clone(), env::signer_account_pk());
+        emit_voter_registered(&signer, self.get_vote_power(&signer).unwrap_or_default());
+        self.voters_index.push(signer);
         self.total_voters += 1;
 
         self.voters.flush();
+        self.voters_index.flush();
         require!(
-            finalize_storage_check(storage, SNAPSHOT_RECORD_COST),
+            finalize_storage_check(storage, record_cost),
             STORAGE_LIMIT_EXCEEDED
         );
     }
@@ -120,27 +194,35 @@ impl Contract {
     /// *Transaction*: Registers the user as a voter with the given public key
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - The contract should be in the registration phase
-    /// - User should be eligible
+    /// - User should be eligible (bulk loaded or proven with the snapshot proof)
     /// - User should not be registered before
     /// - User should pay for storage including the snapshot record cost
     #[payable]
-    pub fn register_as_voter_with_pubkey(&mut self, public_key: PublicKey) {
+    pub fn register_as_voter_with_pubkey(
+        &mut self,
+        public_key: PublicKey,
+        proof: Option<SnapshotProof>,
+    ) {
         let storage = env::storage_usage();
 
         self.try_move_stage();
 
         let user = env::predecessor_account_id();
-        self.assert_eligible_voter(&user);
         require!(!self.voters.contains_key(&user), ALREADY_REGISTERED);
+        let record_cost = self.assert_eligible_voter(&user, proof);
 
-        self.voters.insert(user, public_key);
+        self.voters.insert(user.clone(), public_key);
+        emit_voter_registered(&user, self.get_vote_power(&user).unwrap_or_default());
+        self.voters_index.push(user);
         self.total_voters += 1;
 
         self.voters.flush();
+        self.voters_index.flush();
 
         require!(
-            finalize_storage_check(storage, SNAPSHOT_RECORD_COST),
+            finalize_storage_check(storage, record_cost),
             STORAGE_LIMIT_EXCEEDED
         );
     }
@@ -148,46 +230,103 @@ impl Contract {
     /// *Transaction*: Changes the public key of the user
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - User should be registered
     pub fn change_public_key(&mut self, public_key: PublicKey) {
+        self.assert_not_paused();
         let user = env::predecessor_account_id();
         require!(self.voters.contains_key(&user), NOT_REGISTERED);
 
+        emit_public_key_changed(&user, &public_key);
         self.voters.set(user, Some(public_key));
     }
 
     /// *Transaction*: Registers the user as a nominee
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - The contract should be in the registration phase
-    /// - User should be eligible
+    /// - User should be eligible (bulk loaded or proven with the snapshot proof)
     /// - User should not be registered before
+    /// - The profile should fit the length limits and name one of the houses
     /// - User should pay for storage
     #[payable]
-    pub fn register_as_nominee(&mut self) {
+    pub fn register_as_nominee(&mut self, profile: NomineeProfile, proof: Option<SnapshotProof>) {
         let storage = env::storage_usage();
 
         self.try_move_stage();
 
         let user = env::predecessor_account_id();
-        self.assert_eligible_voter(&user);
-        require!(!self.nominees.contains(&user), ALREADY_REGISTERED);
+        require!(!self.nominees.contains_key(&user), ALREADY_REGISTERED);
+        self.assert_nominee_profile(&profile);
+        self.assert_eligible_voter(&user, proof);
 
-        self.nominees.insert(user);
+        emit_nominee_registered(&user, &profile.house);
+        self.nominees.insert(user, profile);
 
+        self.nominees.flush();
         require!(finalize_storage_check(storage, 0), STORAGE_LIMIT_EXCEEDED);
     }
 
+    /// *Transaction*: Updates the profile of the nominee
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - The contract should be in the registration phase
+    /// - User should be a nominee
+    /// - The profile should fit the length limits and name one of the houses
+    /// - User should pay for the extra storage. Released storage is refunded.
+    #[payable]
+    pub fn update_nominee_profile(&mut self, profile: NomineeProfile) {
+        let storage = env::storage_usage();
+
+        self.try_move_stage();
+        self.assert_registration();
+
+        let user = env::predecessor_account_id();
+        require!(self.nominees.contains_key(&user), NOT_NOMINEE);
+        self.assert_nominee_profile(&profile);
+
+        self.nominees.insert(user, profile);
+
+        self.nominees.flush();
+        if env::storage_usage() > storage {
+            require!(finalize_storage_check(storage, 0), STORAGE_LIMIT_EXCEEDED);
+        } else {
+            refund_released_storage(storage);
+        }
+    }
+
+    /// *Transaction*: Withdraws the nomination. The storage deposit is refunded.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - The contract should be in the registration phase
+    /// - User should be a nominee
+    pub fn withdraw_nomination(&mut self) {
+        let storage = env::storage_usage();
+
+        self.try_move_stage();
+        self.assert_registration();
+
+        let user = env::predecessor_account_id();
+        require!(self.nominees.remove(&user).is_some(), NOT_NOMINEE);
+
+        self.nominees.flush();
+        refund_released_storage(storage);
+    }
+
     /// *Transaction*: Any user can challenge the snapshot. User can deposit NEAR several times
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - The contract should be in the snapshot challenge phase
     /// - User should deposit more than 1 milli NEAR
     #[payable]
     pub fn challenge_snapshot(&mut self) {
         self.try_move_stage();
         require!(
-            matches!(self.status, Status::SnapshotChallenge(_)),
+            matches!(*self.status, Status::SnapshotChallenge(_)),
             ON_SNAPSHOT_CHALLENGE_ONLY
         );
 
@@ -195,87 +334,212 @@ impl Contract {
         let deposit = env::attached_deposit();
 
         require!(deposit.as_millinear() > 0, EXPECTED_DEPOSIT);
+        require!(
+            self.refund_states.get(&user) != Some(&RefundState::PendingRefund),
+            REFUND_PENDING
+        );
+        self.refund_states.insert(user.clone(), RefundState::Active);
+
+        let attempt_deposit = self
+            .attempt_challenges
+            .entry((self.status.attempt(), user.clone()))
+            .or_insert(NearToken::from_yoctonear(0));
+        let is_new_challenger = attempt_deposit.is_zero();
+        *attempt_deposit = attempt_deposit.saturating_add(deposit);
+
+        let record = self.current_attempt();
+        record.total_challenged = record.total_challenged.saturating_add(deposit);
+        if is_new_challenger {
+            record.total_challengers += 1;
+        }
 
-        self.challengers
-            .entry(user)
-            .and_modify(|user_deposit| {
-                if let Some(new_total) = user_deposit.checked_add(deposit) {
-                    *user_deposit = new_total;
-                } else {
-                    env::panic_str(CHALLENGE_OVERFLOW);
-                }
+        let user_deposit = self
+            .challengers
+            .get(&user)
+            .map_or(Some(deposit), |user_deposit| {
+                user_deposit.checked_add(deposit)
             })
-            .or_insert(deposit);
+            .unwrap_or_else(|| env::panic_str(CHALLENGE_OVERFLOW));
+        self.challengers.insert(user.clone(), user_deposit);
         if let Some(total) = self.total_challenged.checked_add(deposit) {
             self.total_challenged = total;
         } else {
             env::panic_str(CHALLENGE_OVERFLOW);
         }
+        emit_snapshot_challenged(self.status.attempt(), &user, deposit, self.total_challenged);
 
         self.try_halt();
     }
 
+    /// *Transaction*: Submits the evidence of what is wrong with the snapshot
+    ///
+    /// The admin uses the evidence to prepare the corrected snapshot.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - The contract should be in the snapshot challenge or halted phase
+    /// - User should challenge the snapshot in the current attempt
+    /// - The evidence should fit the length limits and have the reason
+    /// - User should pay for storage
+    #[payable]
+    pub fn add_challenge_evidence(&mut self, evidence: ChallengeEvidence) {
+        let storage = env::storage_usage();
+
+        self.try_move_stage();
+        require!(
+            matches!(
+                *self.status,
+                Status::SnapshotChallenge(_) | Status::SnapshotHalted(_)
+            ),
+            ON_SNAPSHOT_CHALLENGE_ONLY
+        );
+        require!(evidence.is_valid(), INVALID_CHALLENGE_EVIDENCE);
+
+        let challenger = env::predecessor_account_id();
+        let attempt = self.status.attempt();
+        require!(
+            self.attempt_challenges
+                .contains_key(&(attempt, challenger.clone())),
+            NOT_CHALLENGER
+        );
+
+        emit_challenge_evidence(attempt, &challenger, &evidence);
+        self.challenge_evidence.push(EvidenceRecord {
+            challenger,
+            evidence,
+            submitted_at: env::block_timestamp_ms(),
+        });
+        self.current_attempt().total_evidence += 1;
+
+        self.challenge_evidence.flush();
+        require!(finalize_storage_check(storage, 0), STORAGE_LIMIT_EXCEEDED);
+    }
+
     /// *Transaction*: Refunds the challenge deposit to the user
+    /// together with the user's share of the admin bond in the halted attempts
     ///
     /// Requirements:
+    /// - The contract should not be paused
     /// - The contract should not be in the snapshot challenge phase
-    /// - User should have a deposit
+    /// - User should have a deposit or an unclaimed reward
+    /// - The previous refund of the user should be resolved
     pub fn refund_bond(&mut self) -> Promise {
         self.try_move_stage();
 
         require!(
-            !matches!(self.status, Status::SnapshotChallenge(_)),
+            !matches!(*self.status, Status::SnapshotChallenge(_)),
             NOT_ON_SNAPSHOT_CHALLENGE
         );
 
         let user = env::predecessor_account_id();
-        let deposit = self.challengers.get(&user);
+        require!(!self.is_refund_pending(&user), REFUND_PENDING);
 
-        if let Some(deposit) = deposit {
-            Promise::new(user.clone()).transfer(*deposit).then(
-                ext::ext_self::ext(env::current_account_id())
-                    .with_static_gas(ON_REFUND_SUCCESS_GAS)
-                    .on_refund_success(user),
-            )
-        } else {
-            env::panic_str(NO_DEPOSIT)
+        self.schedule_refund(user)
+            .unwrap_or_else(|| env::panic_str(NO_DEPOSIT))
+    }
+
+    /// *Transaction*: Refunds the deposits and rewards of the challengers in a batch.
+    /// Anyone can call it, so no funds are left locked in the contract after the election.
+    ///
+    /// Challengers with a pending refund are skipped.
+    /// Returns the number of scheduled refunds.
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
+    /// - The contract should not be in the snapshot challenge phase
+    /// - The limit should be greater than zero
+    pub fn refund_all(&mut self, limit: u32) -> u32 {
+        require!(limit > 0, ZERO_LIMIT);
+        self.try_move_stage();
+
+        require!(
+            !matches!(*self.status, Status::SnapshotChallenge(_)),
+            NOT_ON_SNAPSHOT_CHALLENGE
+        );
+
+        let users: Vec<AccountId> = self
+            .challengers
+            .keys()
+            .filter(|user| !self.is_refund_pending(user))
+            .take(limit as usize)
+            .cloned()
+            .collect();
+
+        let mut scheduled = 0;
+        for user in users {
+            // Each refund should leave enough gas for its callback
+            if env::prepaid_gas().saturating_sub(env::used_gas()) < REFUND_BATCH_ITEM_GAS {
+                break;
+            }
+            if self.schedule_refund(user).is_some() {
+                scheduled += 1;
+            }
         }
+        scheduled
     }
 
     /// *Callback*: Callback function to handle the refund success
     ///
     /// Private function
     #[private]
-    pub fn on_refund_success(&mut self, account_id: AccountId) {
+    pub fn on_refund_success(
+        &mut self,
+        account_id: AccountId,
+        amount: NearToken,
+        rewarded_attempts: Vec<u32>,
+    ) {
         require!(env::promise_results_count() == 1, EXPECTED_PROMISE_RESULT);
 
         match env::promise_result(0) {
             PromiseResult::Successful(_) => {
                 // We are not interested in total challenged as we are passed the challenge phase
                 self.challengers.remove(&account_id);
+                emit_bond_refunded(&account_id, amount);
+                self.refund_states.insert(account_id, RefundState::Refunded);
+            }
+            PromiseResult::Failed => {
+                // The user without a deposit claimed only the rewards after the refund
+                let state = if self.challengers.contains_key(&account_id) {
+                    RefundState::Active
+                } else {
+                    RefundState::Refunded
+                };
+                self.refund_states.insert(account_id.clone(), state);
+                for attempt in rewarded_attempts {
+                    self.claimed_rewards.remove(&(attempt, account_id.clone()));
+                }
             }
-            PromiseResult::Failed => {}
         }
     }
 
     /// *Transaction*: Tries to move the status to the next phase
+    ///
+    /// Requirements:
+    /// - The contract should not be paused
     pub fn try_move_stage(&mut self) {
+        self.assert_not_paused();
         let should_move = env::block_timestamp_ms() >= self.end_time_in_millis;
 
-        match self.status {
+        match *self.status {
             Status::SnapshotChallenge(attempt) if should_move => {
                 // Last try to halt
                 if !self.try_halt() {
-                    self.status = Status::Registration(attempt);
+                    // The snapshot has passed the challenge
+                    self.return_admin_bond();
+                    *self.status = Status::Registration(attempt);
+                    let registration_start = self.end_time_in_millis;
+                    self.current_attempt().registration_started_at = Some(registration_start);
 
                     // We don't use block_timestamp_ms() here to have strict timings
                     self.end_time_in_millis += self.process_config.registration_timeout_in_millis;
-                    emit_phase_change(self.status);
+                    emit_phase_change(*self.status, self.snapshot_metadata.as_ref());
                 }
             }
             Status::Registration(attempt) if should_move => {
-                self.status = Status::RegistrationEnded(attempt);
-                emit_phase_change(self.status);
+                *self.status = Status::RegistrationEnded(attempt);
+                let registration_end = self.end_time_in_millis;
+                self.current_attempt().ended_at = Some(registration_end);
+                emit_phase_change(*self.status, self.snapshot_metadata.as_ref());
             }
             // Explicitly write all cases to fail on new status
             Status::Initialization(_)
@@ -286,20 +550,139 @@ impl Contract {
         }
     }
 
-    fn assert_eligible_voter(&self, user: &AccountId) {
+    /// Checks that the user is a part of the snapshot.
+    /// If the user was not bulk loaded, the provided proof is verified against the snapshot root
+    /// and the snapshot record is stored at the user's expense.
+    ///
+    /// Returns the storage that the user should additionally pay for the bulk loaded record.
+    fn assert_eligible_voter(
+        &mut self,
+        user: &AccountId,
+        proof: Option<SnapshotProof>,
+    ) -> StorageUsage {
+        self.assert_registration();
+        if self.eligible_voters.contains_key(user) {
+            return SNAPSHOT_RECORD_COST;
+        }
+
+        let proof = proof.unwrap_or_else(|| env::panic_str(NOT_ELIGIBLE_VOTER));
+        let root = self
+            .snapshot_root
+            .unwrap_or_else(|| env::panic_str(NO_SNAPSHOT_ROOT));
+        require!(
+            merkle::verify_proof(&root, user, &proof.user_data, &proof.path),
+            INVALID_SNAPSHOT_PROOF
+        );
+
+        self.eligible_voters
+            .insert(user.clone(), proof.user_data.into());
+        self.total_eligible_users += 1;
+        self.eligible_voters.flush();
+        0
+    }
+
+    fn assert_nominee_profile(&self, profile: &NomineeProfile) {
+        require!(profile.is_valid(), INVALID_NOMINEE_PROFILE);
+        require!(
+            self.houses.iter().any(|house| house.name == profile.house),
+            UNKNOWN_HOUSE
+        );
+    }
+
+    fn assert_not_paused(&self) {
+        require!(self.paused_at.is_none(), PAUSED);
+    }
+
+    fn assert_registration(&self) {
         require!(
-            matches!(self.status, Status::Registration(_),),
+            matches!(*self.status, Status::Registration(_),),
             ON_REGISTRATION_ONLY
         );
-        require!(self.eligible_voters.contains_key(user), NOT_ELIGIBLE_VOTER);
+    }
+
+    fn current_attempt(&mut self) -> &mut AttemptRecord {
+        let attempt = self.status.attempt();
+        self.attempts
+            .get_mut(attempt)
+            .unwrap_or_else(|| env::abort())
+    }
+
+    fn is_refund_pending(&self, user: &AccountId) -> bool {
+        self.refund_states.get(user) == Some(&RefundState::PendingRefund)
+    }
+
+    /// Transfers the deposit and unclaimed rewards to the user.
+    /// The refund and rewards are marked before the transfer and rolled back if it fails.
+    ///
+    /// Returns None if there is nothing to refund.
+    fn schedule_refund(&mut self, user: AccountId) -> Option<Promise> {
+        let deposit = self
+            .challengers
+            .get(&user)
+            .copied()
+            .unwrap_or(NearToken::from_yoctonear(0));
+        let (reward, rewarded_attempts) = self.unclaimed_rewards(&user);
+        let amount = deposit.saturating_add(reward);
+        if amount.is_zero() {
+            return None;
+        }
+
+        self.refund_states
+            .insert(user.clone(), RefundState::PendingRefund);
+        for attempt in &rewarded_attempts {
+            self.claimed_rewards.insert((*attempt, user.clone()));
+        }
+
+        Some(
+            Promise::new(user.clone()).transfer(amount).then(
+                ext::ext_self::ext(env::current_account_id())
+                    .with_static_gas(ON_REFUND_SUCCESS_GAS)
+                    .on_refund_success(user, amount, rewarded_attempts),
+            ),
+        )
+    }
+
+    /// Returns the share of the admin bond that the user can claim in the halted attempts
+    /// and the attempts it was collected from
+    fn unclaimed_rewards(&self, user: &AccountId) -> (NearToken, Vec<u32>) {
+        let mut total = NearToken::from_yoctonear(0);
+        let mut rewarded_attempts = Vec::new();
+        for (attempt, record) in (0..).zip(self.attempts.iter()) {
+            let key = (attempt, user.clone());
+            let reward = self
+                .attempt_challenges
+                .get(&key)
+                .map_or(NearToken::from_yoctonear(0), |deposit| {
+                    record.reward(*deposit)
+                });
+            if !reward.is_zero() && !self.claimed_rewards.contains(&key) {
+                total = total.saturating_add(reward);
+                rewarded_attempts.push(attempt);
+            }
+        }
+        (total, rewarded_attempts)
+    }
+
+    fn return_admin_bond(&mut self) {
+        let bond = self.current_attempt().admin_bond;
+        if !bond.is_zero() {
+            Promise::new(self.admin.clone()).transfer(bond);
+        }
+    }
+
+    fn halt_attempt(&mut self, reason: HaltReason) {
+        let record = self.current_attempt();
+        record.ended_at = Some(env::block_timestamp_ms());
+        record.halt_reason = Some(reason);
     }
 
     fn try_halt(&mut self) -> bool {
         if self.total_challenged.as_near()
             >= self.process_config.challenge_threshold_in_nears as u128
         {
-            self.status = Status::SnapshotHalted(self.status.attempt());
-            emit_phase_change(self.status);
+            *self.status = Status::SnapshotHalted(self.status.attempt());
+            self.halt_attempt(HaltReason::ChallengeThreshold);
+            emit_phase_change(*self.status, self.snapshot_metadata.as_ref());
             true
         } else {
             false
@@ -311,9 +694,50 @@ impl Contract {
 mod tests {
     use std::str::FromStr;
 
-    use near_sdk::{testing_env, NearToken, PublicKey};
+    use near_sdk::{
+        mock::MockAction,
+        test_utils::{get_created_receipts, get_logs},
+        testing_env, AccountId, NearToken, PromiseResult, PublicKey,
+    };
+
+    use crate::{
+        consts::MAX_LINKS,
+        test_utils::*,
+        types::{
+            AttemptRecord, ChallengeEvidence, EvidenceRecord, HaltReason, RefundState,
+            SnapshotProof, Status, UserData,
+        },
+        Contract,
+    };
+
+    fn transferred_to(receiver: &AccountId) -> NearToken {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == receiver)
+            .flat_map(|receipt| receipt.actions)
+            .fold(NearToken::from_near(0), |total, action| match action {
+                MockAction::Transfer { deposit, .. } => total.saturating_add(deposit),
+                _ => total,
+            })
+    }
+
+    fn commit_snapshot_root(
+        context: &mut near_sdk::VMContext,
+        contract: &mut Contract,
+    ) -> Vec<(AccountId, SnapshotProof)> {
+        let records = load_voters();
+        let (root, paths) = merkle_tree(&records);
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.set_snapshot_root(Some(root.into()));
 
-    use crate::{test_utils::*, types::Status};
+        records
+            .into_iter()
+            .zip(paths)
+            .map(|((account_id, user_data), path)| (account_id, SnapshotProof { user_data, path }))
+            .collect()
+    }
 
     #[test]
     fn eligible_user_can_register_as_voter() {
@@ -327,9 +751,10 @@ mod tests {
         context.signer_account_pk = pk();
         testing_env!(context.clone());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
 
         assert!(contract.is_voter(&acc(1)));
+        assert!(get_logs()[0].contains(r#""event":"voter_registered""#));
     }
 
     #[test]
@@ -346,7 +771,7 @@ mod tests {
         context.attached_deposit = NearToken::from_yoctonear(0);
         testing_env!(context.clone());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
     }
 
     #[test]
@@ -361,7 +786,7 @@ mod tests {
         context.attached_deposit = NearToken::from_yoctonear(0);
         testing_env!(context.clone());
 
-        contract.register_as_voter_with_pubkey(pk());
+        contract.register_as_voter_with_pubkey(pk(), None);
     }
 
     #[test]
@@ -376,7 +801,7 @@ mod tests {
         context.attached_deposit = NearToken::from_yoctonear(0);
         testing_env!(context.clone());
 
-        contract.register_as_nominee();
+        contract.register_as_nominee(profile(), None);
     }
 
     #[test]
@@ -392,7 +817,7 @@ mod tests {
         context.signer_account_pk = pk();
         testing_env!(context.clone());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
     }
 
     #[test]
@@ -408,7 +833,7 @@ mod tests {
         context.signer_account_pk = pk();
         testing_env!(context.clone());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
     }
 
     #[test]
@@ -421,7 +846,7 @@ mod tests {
         context.predecessor_account_id = acc(1);
         testing_env!(context.clone());
 
-        contract.register_as_voter_with_pubkey(pk());
+        contract.register_as_voter_with_pubkey(pk(), None);
 
         assert!(contract.is_voter(&acc(1)));
     }
@@ -437,7 +862,7 @@ mod tests {
         context.predecessor_account_id = acc(0);
         testing_env!(context.clone());
 
-        contract.register_as_voter_with_pubkey(pk());
+        contract.register_as_voter_with_pubkey(pk(), None);
     }
 
     #[test]
@@ -452,7 +877,7 @@ mod tests {
         context.signer_account_pk = pk();
         testing_env!(context.clone());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
 
         assert_eq!(
             contract.get_voter_information(&acc(1)).unwrap().public_key,
@@ -480,9 +905,159 @@ mod tests {
         context.predecessor_account_id = acc(1);
         testing_env!(context.clone());
 
-        contract.register_as_nominee();
+        contract.register_as_nominee(profile(), None);
 
         assert!(contract.is_nominee(&acc(1)));
+        assert_eq!(contract.get_nominee_profile(&acc(1)), Some(profile()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid nominee profile")]
+    fn user_cannot_register_as_nominee_with_too_many_links() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        let mut profile = profile();
+        profile.links = vec!["https://near.org".to_string(); MAX_LINKS + 1];
+        contract.register_as_nominee(profile, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown house")]
+    fn user_cannot_register_as_nominee_into_unknown_house() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        let mut profile = profile();
+        profile.house = "House of Lords".to_string();
+        contract.register_as_nominee(profile, None);
+    }
+
+    #[test]
+    fn nominee_can_update_profile() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        contract.register_as_nominee(profile(), None);
+
+        let mut updated = profile();
+        updated.platform = "Shorter platform".to_string();
+        updated.house = "Council of Advisors".to_string();
+        contract.update_nominee_profile(updated.clone());
+
+        assert_eq!(contract.get_nominee_profile(&acc(1)), Some(updated));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a nominee")]
+    fn non_nominee_cannot_update_profile() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        contract.update_nominee_profile(profile());
+    }
+
+    #[test]
+    fn nominee_can_withdraw_with_storage_refund() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        contract.register_as_nominee(profile(), None);
+
+        context.attached_deposit = NearToken::from_yoctonear(0);
+        testing_env!(context.clone());
+
+        contract.withdraw_nomination();
+
+        assert!(!contract.is_nominee(&acc(1)));
+        assert_eq!(contract.get_nominee_profile(&acc(1)), None);
+
+        let receipts = get_created_receipts();
+        assert!(receipts.iter().any(|receipt| receipt.receiver_id == acc(1)
+            && receipt.actions.iter().any(|action| matches!(
+                action,
+                MockAction::Transfer { deposit, .. } if deposit.as_yoctonear() > 0
+            ))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Allowed only during registration phase")]
+    fn nominee_cannot_withdraw_after_registration() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        contract.register_as_nominee(profile(), None);
+
+        move_to_end(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(1);
+        testing_env!(context.clone());
+
+        contract.withdraw_nomination();
+    }
+
+    #[test]
+    fn nominees_are_listed_with_pagination() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(load_voters());
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        for i in 1..=4 {
+            context.predecessor_account_id = acc(i);
+            testing_env!(context.clone());
+            contract.register_as_nominee(profile(), None);
+        }
+
+        context.predecessor_account_id = acc(2);
+        testing_env!(context.clone());
+        contract.withdraw_nomination();
+
+        let nominees: Vec<AccountId> = contract
+            .get_nominees(0, 10)
+            .into_iter()
+            .map(|(nominee, _)| nominee)
+            .collect();
+        // The withdrawn nominee is replaced with the last registered one
+        assert_eq!(nominees, vec![acc(1), acc(4), acc(3)]);
+
+        let page = contract.get_nominees(1, 1);
+        assert_eq!(page, vec![(acc(4), profile())]);
     }
 
     #[test]
@@ -496,7 +1071,7 @@ mod tests {
         context.predecessor_account_id = acc(0);
         testing_env!(context.clone());
 
-        contract.register_as_nominee();
+        contract.register_as_nominee(profile(), None);
     }
 
     #[test]
@@ -511,7 +1086,7 @@ mod tests {
         context.predecessor_account_id = acc(1);
         testing_env!(context.clone());
 
-        contract.register_as_nominee();
+        contract.register_as_nominee(profile(), None);
     }
 
     #[test]
@@ -526,7 +1101,7 @@ mod tests {
         context.predecessor_account_id = acc(1);
         testing_env!(context.clone());
 
-        contract.register_as_voter_with_pubkey(pk());
+        contract.register_as_voter_with_pubkey(pk(), None);
     }
 
     #[test]
@@ -541,7 +1116,7 @@ mod tests {
         context.signer_account_pk = pk();
         testing_env!(context.clone());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
     }
 
     #[test]
@@ -554,7 +1129,7 @@ mod tests {
         context.predecessor_account_id = acc(1);
         testing_env!(context.clone());
 
-        contract.register_as_nominee();
+        contract.register_as_nominee(profile(), None);
     }
 
     #[test]
@@ -633,4 +1208,563 @@ mod tests {
         assert!(matches!(contract.get_status(), Status::Initialization(1)));
         assert_eq!(contract.get_total_challenge(), NearToken::from_millinear(0));
     }
+
+    #[test]
+    fn challenger_can_submit_evidence() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.add_challenge_evidence(evidence());
+
+        assert_eq!(
+            contract.get_challenge_evidence(0, 0, 10),
+            vec![EvidenceRecord {
+                challenger: acc(0),
+                evidence: evidence(),
+                submitted_at: START,
+            }]
+        );
+        assert_eq!(contract.get_attempt(0).unwrap().total_evidence, 1);
+        assert!(get_logs()[0].contains(r#""event":"challenge_evidence""#));
+        // The challenge amount is not affected by the storage deposit
+        assert_eq!(contract.get_total_challenge(), NearToken::from_near(1));
+    }
+
+    #[test]
+    fn evidence_is_listed_per_attempt() {
+        let (mut context, mut contract) = setup_ctr();
+        let threshold = contract.get_process_config().challenge_threshold_in_nears as u128;
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(threshold);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+        assert!(matches!(contract.get_status(), Status::SnapshotHalted(0)));
+
+        // The evidence can be submitted after the halt
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.add_challenge_evidence(evidence());
+        contract.add_challenge_evidence(ChallengeEvidence {
+            accounts: vec![],
+            reason: "Wrong block height".to_string(),
+            proof_link: None,
+        });
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.restart_to_initialization();
+
+        assert_eq!(contract.get_challenge_evidence(0, 1, 10).len(), 1);
+        assert_eq!(
+            contract.get_challenge_evidence(0, 1, 10)[0].evidence.reason,
+            "Wrong block height"
+        );
+        assert!(contract.get_challenge_evidence(1, 0, 10).is_empty());
+        assert_eq!(contract.get_attempt(1).unwrap().first_evidence, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only challengers of the current attempt can submit evidence")]
+    fn non_challenger_cannot_submit_evidence() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.add_challenge_evidence(evidence());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid challenge evidence")]
+    fn challenger_cannot_submit_evidence_without_reason() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+
+        contract.add_challenge_evidence(ChallengeEvidence {
+            reason: String::new(),
+            ..evidence()
+        });
+    }
+
+    #[test]
+    fn halted_attempt_is_kept_in_history() {
+        let (mut context, mut contract) = setup_ctr();
+        commit_snapshot_root(&mut context, &mut contract);
+        let threshold = contract.get_process_config().challenge_threshold_in_nears as u128;
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+        contract.challenge_snapshot();
+
+        context.predecessor_account_id = acc(1);
+        context.attached_deposit = NearToken::from_near(threshold - 2);
+        context.block_timestamp += MSECOND;
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.restart_to_initialization();
+
+        let attempts = contract.get_attempts();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].snapshot_root, contract.get_snapshot_root());
+        assert!(attempts[0].snapshot_root.is_some());
+        assert_eq!(attempts[0].challenge_started_at, Some(START));
+        assert_eq!(attempts[0].ended_at, Some(START + 1));
+        assert_eq!(
+            attempts[0].halt_reason,
+            Some(HaltReason::ChallengeThreshold)
+        );
+        assert_eq!(
+            attempts[0].total_challenged,
+            NearToken::from_near(threshold)
+        );
+        assert_eq!(attempts[0].total_challengers, 2);
+        assert_eq!(
+            contract.get_attempt_challenge(0, acc(0)),
+            Some(NearToken::from_near(2))
+        );
+
+        assert_eq!(
+            contract.get_attempt(1),
+            Some(AttemptRecord::new(START + 1, 0))
+        );
+        assert_eq!(contract.get_attempt(2), None);
+    }
+
+    #[test]
+    fn restart_during_challenge_is_recorded() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        contract.restart_to_initialization();
+
+        let attempt = contract.get_attempt(0).unwrap();
+        assert_eq!(attempt.halt_reason, Some(HaltReason::AdminRestart));
+        assert_eq!(attempt.ended_at, Some(START));
+    }
+
+    #[test]
+    fn halted_attempt_bond_is_split_between_challengers() {
+        let (mut context, mut contract) = setup_ctr();
+        let threshold = contract.get_process_config().challenge_threshold_in_nears as u128;
+        let bond = contract.get_process_config().admin_bond_in_nears as u128;
+
+        move_to_challenge(&mut context, &mut contract);
+        assert_eq!(
+            contract.get_attempt(0).unwrap().admin_bond,
+            NearToken::from_near(bond)
+        );
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(threshold / 4);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+        assert_eq!(
+            contract.get_challenger_reward(&acc(0)),
+            NearToken::from_near(0)
+        );
+
+        context.predecessor_account_id = acc(1);
+        context.attached_deposit = NearToken::from_near(threshold - threshold / 4);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+        assert!(matches!(contract.get_status(), Status::SnapshotHalted(_)));
+
+        let reward = NearToken::from_yoctonear(NearToken::from_near(bond).as_yoctonear() / 4);
+        assert_eq!(contract.get_challenger_reward(&acc(0)), reward);
+        assert_eq!(
+            contract.get_challenger_reward(&acc(1)),
+            NearToken::from_near(bond).saturating_sub(reward)
+        );
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(0);
+        testing_env!(context.clone());
+        contract.refund_bond();
+
+        assert_eq!(
+            transferred_to(&acc(0)),
+            NearToken::from_near(threshold / 4).saturating_add(reward)
+        );
+        // The reward is claimed together with the deposit
+        assert_eq!(
+            contract.get_challenger_reward(&acc(0)),
+            NearToken::from_near(0)
+        );
+    }
+
+    #[test]
+    fn admin_bond_is_returned_when_snapshot_passes() {
+        let (mut context, mut contract) = setup_ctr();
+        let bond = contract.get_process_config().admin_bond_in_nears as u128;
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+
+        move_to_registration(&mut context, &mut contract);
+
+        assert_eq!(transferred_to(&admin()), NearToken::from_near(bond));
+        assert_eq!(
+            contract.get_challenger_reward(&acc(0)),
+            NearToken::from_near(0)
+        );
+    }
+
+    #[test]
+    fn admin_bond_goes_to_challengers_on_restart_during_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+        let bond = contract.get_process_config().admin_bond_in_nears as u128;
+
+        move_to_challenge(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        context.attached_deposit = NearToken::from_near(1);
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_near(0);
+        testing_env!(context.clone());
+        contract.restart_to_initialization();
+
+        assert_eq!(transferred_to(&admin()), NearToken::from_near(0));
+        assert_eq!(
+            contract.get_challenger_reward(&acc(0)),
+            NearToken::from_near(bond)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit should be equal to the admin bond")]
+    fn admin_cannot_start_challenge_without_bond() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        testing_env!(context.clone());
+        contract.start_challenge();
+    }
+
+    fn halt_by(context: &mut near_sdk::VMContext, contract: &mut Contract, challenger: AccountId) {
+        move_to_challenge(context, contract);
+
+        context.predecessor_account_id = challenger;
+        context.attached_deposit = NearToken::from_near(
+            contract.get_process_config().challenge_threshold_in_nears as u128,
+        );
+        testing_env!(context.clone());
+        contract.challenge_snapshot();
+        assert!(matches!(contract.get_status(), Status::SnapshotHalted(_)));
+
+        // The contract holds the admin bond and the deposit
+        context.account_balance = NearToken::from_near(1000);
+        context.attached_deposit = NearToken::from_near(0);
+        testing_env!(context.clone());
+    }
+
+    fn resolve_refund(
+        context: &mut near_sdk::VMContext,
+        contract: &mut Contract,
+        account_id: AccountId,
+        result: PromiseResult,
+    ) {
+        let caller = context.predecessor_account_id.clone();
+        context.predecessor_account_id = context.current_account_id.clone();
+        testing_env!(
+            context.clone(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+        let amount = contract
+            .get_individual_challenge(&account_id)
+            .unwrap_or(NearToken::from_yoctonear(0));
+        contract.on_refund_success(account_id, amount, vec![0]);
+
+        context.predecessor_account_id = caller;
+        testing_env!(context.clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "Refund is pending for the user")]
+    fn concurrent_refund_is_rejected_while_pending() {
+        let (mut context, mut contract) = setup_ctr();
+        halt_by(&mut context, &mut contract, acc(0));
+        assert_eq!(
+            contract.get_refund_state(&acc(0)),
+            Some(RefundState::Active)
+        );
+
+        contract.refund_bond();
+        assert_eq!(
+            contract.get_refund_state(&acc(0)),
+            Some(RefundState::PendingRefund)
+        );
+
+        // The second call before the callback would transfer the same deposit again
+        contract.refund_bond();
+    }
+
+    #[test]
+    fn failed_refund_is_rolled_back() {
+        let (mut context, mut contract) = setup_ctr();
+        halt_by(&mut context, &mut contract, acc(0));
+        let deposit = contract.get_individual_challenge(&acc(0));
+        let reward = contract.get_challenger_reward(&acc(0));
+
+        contract.refund_bond();
+        resolve_refund(&mut context, &mut contract, acc(0), PromiseResult::Failed);
+
+        assert_eq!(
+            contract.get_refund_state(&acc(0)),
+            Some(RefundState::Active)
+        );
+        assert_eq!(contract.get_individual_challenge(&acc(0)), deposit);
+        assert_eq!(contract.get_challenger_reward(&acc(0)), reward);
+
+        // The user can retry the refund after the rollback
+        contract.refund_bond();
+        resolve_refund(
+            &mut context,
+            &mut contract,
+            acc(0),
+            PromiseResult::Successful(vec![]),
+        );
+
+        assert_eq!(
+            contract.get_refund_state(&acc(0)),
+            Some(RefundState::Refunded)
+        );
+        assert_eq!(contract.get_individual_challenge(&acc(0)), None);
+        assert_eq!(
+            contract.get_challenger_reward(&acc(0)),
+            NearToken::from_near(0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No deposit found for the user")]
+    fn refunded_user_cannot_refund_again() {
+        let (mut context, mut contract) = setup_ctr();
+        halt_by(&mut context, &mut contract, acc(0));
+
+        contract.refund_bond();
+        resolve_refund(
+            &mut context,
+            &mut contract,
+            acc(0),
+            PromiseResult::Successful(vec![]),
+        );
+
+        contract.refund_bond();
+    }
+
+    #[test]
+    fn anyone_can_refund_all_challengers_in_batches() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        for i in 0..3 {
+            context.predecessor_account_id = acc(i);
+            context.attached_deposit = NearToken::from_near(1);
+            testing_env!(context.clone());
+            contract.challenge_snapshot();
+        }
+
+        move_to_registration(&mut context, &mut contract);
+        context.predecessor_account_id = acc(5);
+        context.attached_deposit = NearToken::from_near(0);
+        testing_env!(context.clone());
+
+        assert_eq!(contract.refund_all(2), 2);
+        assert_eq!(transferred_to(&acc(0)), NearToken::from_near(1));
+        assert_eq!(transferred_to(&acc(1)), NearToken::from_near(1));
+        assert_eq!(
+            contract.get_refund_state(&acc(1)),
+            Some(RefundState::PendingRefund)
+        );
+
+        // Pending refunds are skipped
+        assert_eq!(contract.refund_all(10), 1);
+        assert_eq!(contract.refund_all(10), 0);
+
+        for i in 0..3 {
+            resolve_refund(
+                &mut context,
+                &mut contract,
+                acc(i),
+                PromiseResult::Successful(vec![]),
+            );
+        }
+        assert!(contract.get_challengers(0, 10).is_empty());
+        assert_eq!(
+            contract.get_refund_state(&acc(2)),
+            Some(RefundState::Refunded)
+        );
+    }
+
+    #[test]
+    fn failed_batch_refund_can_be_retried() {
+        let (mut context, mut contract) = setup_ctr();
+        halt_by(&mut context, &mut contract, acc(0));
+
+        context.predecessor_account_id = acc(5);
+        testing_env!(context.clone());
+        assert_eq!(contract.refund_all(10), 1);
+        resolve_refund(&mut context, &mut contract, acc(0), PromiseResult::Failed);
+
+        assert_eq!(
+            contract.get_challengers(0, 10),
+            vec![(
+                acc(0),
+                NearToken::from_near(
+                    contract.get_process_config().challenge_threshold_in_nears as u128
+                )
+            )]
+        );
+        assert_eq!(contract.refund_all(10), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not allowed on snapshot challenge phase")]
+    fn cannot_refund_all_during_challenge() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        contract.refund_all(10);
+    }
+
+    #[test]
+    fn registration_timings_are_recorded() {
+        let (mut context, mut contract) = setup_ctr();
+        let config = contract.get_process_config();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+        move_to_end(&mut context, &mut contract);
+
+        let attempt = contract.get_attempt(0).unwrap();
+        let registration_start = START + config.challenge_timeout_in_millis;
+        assert_eq!(attempt.registration_started_at, Some(registration_start));
+        assert_eq!(
+            attempt.ended_at,
+            Some(registration_start + config.registration_timeout_in_millis)
+        );
+        assert_eq!(attempt.halt_reason, None);
+    }
+
+    #[test]
+    fn user_can_register_with_snapshot_proof() {
+        let (mut context, mut contract) = setup_ctr();
+        let proofs = commit_snapshot_root(&mut context, &mut contract);
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        let (user, proof) = proofs[0].clone();
+        assert!(!contract.is_eligible_voter(&user));
+
+        context.predecessor_account_id = user.clone();
+        testing_env!(context.clone());
+
+        contract.register_as_voter_with_pubkey(pk(), Some(proof.clone()));
+
+        assert!(contract.is_voter(&user));
+        assert_eq!(
+            contract.get_eligible_voter_info(&user),
+            Some(proof.user_data)
+        );
+        // Proven users are counted once they are stored
+        assert_eq!(contract.get_total_eligible_users(), 2);
+
+        let (user, proof) = proofs[1].clone();
+        context.predecessor_account_id = user.clone();
+        testing_env!(context.clone());
+
+        contract.register_as_nominee(profile(), Some(proof));
+
+        assert!(contract.is_nominee(&user));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid snapshot proof")]
+    fn user_cannot_register_with_forged_snapshot_proof() {
+        let (mut context, mut contract) = setup_ctr();
+        let proofs = commit_snapshot_root(&mut context, &mut contract);
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        let (user, mut proof) = proofs[0].clone();
+        proof.user_data = UserData::new(100, NearToken::from_near(1000));
+
+        context.predecessor_account_id = user;
+        testing_env!(context.clone());
+
+        contract.register_as_voter_with_pubkey(pk(), Some(proof));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid snapshot proof")]
+    fn user_cannot_use_proof_of_another_user() {
+        let (mut context, mut contract) = setup_ctr();
+        let proofs = commit_snapshot_root(&mut context, &mut contract);
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(0);
+        testing_env!(context.clone());
+
+        contract.register_as_nominee(profile(), Some(proofs[0].1.clone()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Snapshot root is not set")]
+    fn user_cannot_register_with_proof_without_snapshot_root() {
+        let (mut context, mut contract) = setup_ctr();
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        context.predecessor_account_id = acc(2);
+        testing_env!(context.clone());
+
+        contract.register_as_voter_with_pubkey(
+            pk(),
+            Some(SnapshotProof {
+                user_data: UserData::new(2, NearToken::from_near(2)),
+                path: vec![],
+            }),
+        );
+    }
 }
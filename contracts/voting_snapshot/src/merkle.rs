@@ -0,0 +1,143 @@
+use near_sdk::{borsh, env, json_types::Base58CryptoHash, AccountId, CryptoHash};
+
+use crate::types::UserData;
+
+// Domain separation prefixes to make leaves and internal nodes distinguishable
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Hash of the snapshot record: `sha256(0x00 || borsh(account_id) || borsh(user_data))`
+pub fn leaf_hash(account_id: &AccountId, user_data: &UserData) -> CryptoHash {
+    let mut data = vec![LEAF_PREFIX];
+    data.extend(borsh::to_vec(&(account_id, user_data)).unwrap_or_else(|_| env::abort()));
+    env::sha256_array(&data)
+}
+
+/// Hash of the internal node: `sha256(0x01 || min(a, b) || max(a, b))`
+///
+/// Pairs are sorted, so the proof doesn't need to know the position of the leaf.
+pub fn node_hash(a: &CryptoHash, b: &CryptoHash) -> CryptoHash {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = Vec::with_capacity(1 + 2 * left.len());
+    data.push(NODE_PREFIX);
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    env::sha256_array(&data)
+}
+
+/// Verifies that the record belongs to the snapshot with the given root
+pub fn verify_proof(
+    root: &CryptoHash,
+    account_id: &AccountId,
+    user_data: &UserData,
+    path: &[Base58CryptoHash],
+) -> bool {
+    let computed = path
+        .iter()
+        .fold(leaf_hash(account_id, user_data), |hash, sibling| {
+            node_hash(&hash, &(*sibling).into())
+        });
+    &computed == root
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::NearToken;
+
+    use super::*;
+    use crate::test_utils::{acc, merkle_tree};
+
+    fn records() -> Vec<(AccountId, UserData)> {
+        (0..5)
+            .map(|i| {
+                (
+                    acc(i),
+                    UserData::new(i as u32, NearToken::from_near(i as u128)),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_record_can_be_proven() {
+        let records = records();
+        let (root, proofs) = merkle_tree(&records);
+
+        for ((account_id, user_data), path) in records.iter().zip(proofs) {
+            assert!(verify_proof(&root, account_id, user_data, &path));
+        }
+    }
+
+    #[test]
+    fn modified_record_cannot_be_proven() {
+        let records = records();
+        let (root, proofs) = merkle_tree(&records);
+
+        let forged = UserData::new(100, records[1].1.stake);
+        assert!(!verify_proof(&root, &records[1].0, &forged, &proofs[1]));
+        assert!(!verify_proof(
+            &root,
+            &records[2].0,
+            &records[1].1,
+            &proofs[1]
+        ));
+    }
+
+    #[test]
+    fn truncated_path_is_rejected() {
+        let records = records();
+        let (root, proofs) = merkle_tree(&records);
+
+        // Shorter path ends up on the internal node that is not the root
+        let path = &proofs[0][..proofs[0].len() - 1];
+        assert!(!verify_proof(&root, &records[0].0, &records[0].1, path));
+    }
+
+    // Same vector is checked in `snapshotter/merkle.test.js`
+    #[test]
+    fn snapshotter_test_vector() {
+        let records = vec![
+            (
+                "alice.near".parse().unwrap(),
+                UserData::new(3, NearToken::from_near(1)),
+            ),
+            (
+                "bob.near".parse().unwrap(),
+                UserData::new(0, NearToken::from_yoctonear(0)),
+            ),
+            (
+                "carol.near".parse().unwrap(),
+                UserData::new(12, NearToken::from_yoctonear(u128::MAX)),
+            ),
+        ];
+        let (root, proofs) = merkle_tree(&records);
+
+        assert_eq!(
+            Base58CryptoHash::from(root),
+            "x2R9eueSBQare6tLxDFMSFkVWH5XyNQz8ndFJ5kEQpv"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            proofs[2],
+            vec!["3bYRYRhzxTnpkTGhGHZTwUHV3oJTMXfMNBoo62bS4ULr"
+                .parse()
+                .unwrap()]
+        );
+    }
+
+    #[test]
+    fn single_record_tree() {
+        let records = vec![(acc(1), UserData::new(1, NearToken::from_near(1)))];
+        let (root, proofs) = merkle_tree(&records);
+
+        assert_eq!(root, leaf_hash(&records[0].0, &records[0].1));
+        assert!(proofs[0].is_empty());
+        assert!(verify_proof(
+            &root,
+            &records[0].0,
+            &records[0].1,
+            &proofs[0]
+        ));
+    }
+}
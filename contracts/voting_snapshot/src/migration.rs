@@ -0,0 +1,94 @@
+use near_sdk::borsh::BorshDeserialize;
+use near_sdk::{env, near_bindgen};
+
+use common_contracts::upgrade::{stored_state_version, write_state_version};
+
+use crate::consts::{NO_STATE, STATE_VERSION, UNKNOWN_STATE_VERSION};
+use crate::{Contract, ContractExt};
+
+fn read_state<T: BorshDeserialize>() -> T {
+    env::state_read().unwrap_or_else(|| env::panic_str(NO_STATE))
+}
+
+#[near_bindgen]
+impl Contract {
+    /// *Callback*: Migrates the state of the previous version after the upgrade
+    ///
+    /// Decodes the layout of the stored state version and applies the migrations
+    /// from it to the current version one by one. The current layout is kept as is.
+    /// When the layout changes, the current one is copied as the next `ContractV<n>`
+    /// with a step from it, and `STATE_VERSION` is bumped.
+    ///
+    /// The state of the release before the versioning has no stored version and is not migrated:
+    /// its snapshot records can't be enumerated, so the contract should be deployed from scratch.
+    ///
+    /// Private function
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let contract = match stored_state_version() {
+            Some(STATE_VERSION) => read_state(),
+            _ => env::panic_str(UNKNOWN_STATE_VERSION),
+        };
+        write_state_version(STATE_VERSION);
+
+        contract
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_contracts::upgrade::{read_state_version, STATE_VERSION_KEY};
+    use near_sdk::{testing_env, NearToken};
+
+    use super::*;
+    use crate::test_utils::*;
+    use crate::types::{Status, UserData};
+
+    #[test]
+    fn state_is_migrated() {
+        let (mut context, contract) = setup_ctr();
+        env::state_write(&contract);
+        drop(contract);
+
+        let mut contract = Contract::migrate();
+
+        assert_eq!(read_state_version(), STATE_VERSION);
+        assert_eq!(contract.get_status(), Status::Initialization(0));
+        assert_eq!(contract.get_vote_config(), default_vote_config());
+        assert_eq!(contract.get_process_config(), default_snapshot_config());
+        assert_eq!(contract.get_houses(), houses());
+        assert_eq!(contract.get_snapshot_metadata(), Some(metadata()));
+        assert_eq!(contract.get_total_eligible_users(), 1);
+        assert_eq!(
+            contract.get_eligible_voter_info(&acc(1)),
+            Some(UserData::new(1, NearToken::from_near(1)))
+        );
+
+        // The migrated contract keeps working
+        context.predecessor_account_id = admin();
+        testing_env!(context);
+        contract.bulk_load_voters(load_voters());
+        assert_eq!(contract.get_total_eligible_users(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown state version")]
+    fn unversioned_state_is_not_migrated() {
+        let (_, contract) = setup_ctr();
+        env::state_write(&contract);
+        env::storage_remove(STATE_VERSION_KEY);
+
+        Contract::migrate();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown state version")]
+    fn unknown_state_version_is_not_migrated() {
+        let (_, contract) = setup_ctr();
+        env::state_write(&contract);
+        write_state_version(STATE_VERSION + 1);
+
+        Contract::migrate();
+    }
+}
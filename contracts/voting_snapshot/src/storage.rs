@@ -7,6 +7,13 @@ use near_sdk::BorshStorageKey;
 pub enum StorageKey {
     EligibleVoters,
     Voters,
+    VotersIndex,
     Nominees,
     Challengers,
+    Attempts,
+    AttemptChallenges,
+    ChallengeEvidence,
+    ClaimedRewards,
+    RefundStates,
+    Roles,
 }
@@ -1,7 +1,8 @@
 use std::str::FromStr;
 
+use near_sdk::json_types::Base58CryptoHash;
 use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, VMContext};
-use near_sdk::{NearToken, PublicKey};
+use near_sdk::{CryptoHash, NearToken, PublicKey};
 
 /// 1ms in nano seconds
 pub const MSECOND: u64 = 1_000_000;
@@ -9,7 +10,11 @@ pub const MSECOND: u64 = 1_000_000;
 // In milliseconds
 pub const START: u64 = 1;
 
-use crate::types::{SnapshotConfig, Status, UserData};
+use crate::merkle::{leaf_hash, node_hash};
+use crate::types::{
+    ActivityConfig, AffectedAccount, ChallengeEvidence, House, NomineeProfile, SnapshotConfig,
+    SnapshotMetadata, Status, UserData,
+};
 use crate::{types::VoteWeightConfig, Contract};
 
 pub fn acc(idx: u8) -> AccountId {
@@ -17,23 +22,73 @@ pub fn acc(idx: u8) -> AccountId {
 }
 
 pub fn pk() -> PublicKey {
-    PublicKey::from_str(&"ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp").unwrap()
+    PublicKey::from_str("ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp").unwrap()
 }
 
 pub fn admin() -> AccountId {
     AccountId::from_str("admin.near").unwrap()
 }
 
+pub fn profile() -> NomineeProfile {
+    NomineeProfile {
+        platform: "Transparent and accountable governance".to_string(),
+        house: "House of Merit".to_string(),
+        links: vec!["https://near.social/user.near".to_string()],
+    }
+}
+
+pub fn metadata() -> SnapshotMetadata {
+    SnapshotMetadata {
+        block_height: 108194270,
+        ipfs_cid: "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string(),
+        data_hash: [1; 32].into(),
+        formula_version: 1,
+    }
+}
+
+pub fn evidence() -> ChallengeEvidence {
+    ChallengeEvidence {
+        accounts: vec![AffectedAccount {
+            account_id: acc(1),
+            expected: Some(UserData::new(1, NearToken::from_near(10))),
+        }],
+        reason: "Stake of the lockup is missing".to_string(),
+        proof_link: Some("https://nearblocks.io/address/user-1.near".to_string()),
+    }
+}
+
+pub fn houses() -> Vec<House> {
+    vec![
+        House {
+            name: "House of Merit".to_string(),
+            seats: 15,
+        },
+        House {
+            name: "Council of Advisors".to_string(),
+            seats: 7,
+        },
+    ]
+}
+
+pub fn default_activity_config() -> ActivityConfig {
+    ActivityConfig {
+        activity_reward_in_votes: 10,
+        max_active_months: None,
+    }
+}
+
 pub fn default_vote_config() -> VoteWeightConfig {
-    VoteWeightConfig {
+    VoteWeightConfig::Root {
         threshold_in_nears: 100,
-        activity_reward_in_votes: 10,
+        root: 2,
+        activity: default_activity_config(),
     }
 }
 
 pub fn default_snapshot_config() -> SnapshotConfig {
     SnapshotConfig {
         challenge_threshold_in_nears: 100,
+        admin_bond_in_nears: 10,
         challenge_timeout_in_millis: 60 * 60 * 24 * 7 * 1000,
         registration_timeout_in_millis: 60 * 60 * 24 * 7 * 1000,
     }
@@ -44,9 +99,13 @@ pub fn move_to_challenge(context: &mut VMContext, contract: &mut Contract) {
 
     context.predecessor_account_id = admin();
     context.block_timestamp = START * MSECOND;
+    let deposit = context.attached_deposit;
+    context.attached_deposit =
+        NearToken::from_near(contract.get_process_config().admin_bond_in_nears.into());
     testing_env!(context.clone());
 
     contract.start_challenge();
+    context.attached_deposit = deposit;
     assert!(matches!(
         contract.get_status(),
         Status::SnapshotChallenge(_)
@@ -119,6 +178,39 @@ pub fn load_voters() -> Vec<(AccountId, UserData)> {
     ]
 }
 
+/// Builds the snapshot Merkle tree the same way as the off-chain tooling.
+/// The odd node on the level is promoted to the next level as is.
+///
+/// Returns the root and the proof path for each record
+pub fn merkle_tree(records: &[(AccountId, UserData)]) -> (CryptoHash, Vec<Vec<Base58CryptoHash>>) {
+    let mut level: Vec<CryptoHash> = records
+        .iter()
+        .map(|(account_id, user_data)| leaf_hash(account_id, user_data))
+        .collect();
+    let mut positions: Vec<usize> = (0..records.len()).collect();
+    let mut proofs = vec![vec![]; records.len()];
+
+    while level.len() > 1 {
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            let sibling = *position ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling].into());
+            }
+            *position /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node_hash(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    (level[0], proofs)
+}
+
 pub fn setup_ctr() -> (VMContext, Contract) {
     let mut context = VMContextBuilder::new().build();
 
@@ -128,6 +220,8 @@ pub fn setup_ctr() -> (VMContext, Contract) {
 
     testing_env!(context.clone());
 
+    contract.set_houses(houses());
+    contract.set_snapshot_metadata(metadata());
     contract.bulk_load_voters(vec![(
         acc(1),
         UserData {
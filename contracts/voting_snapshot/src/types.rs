@@ -1,9 +1,19 @@
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
+    json_types::Base58CryptoHash,
     serde::{Deserialize, Serialize},
-    NearSchema, NearToken,
+    AccountId, NearSchema, NearToken,
 };
 
+use common_contracts::events::snapshot::SnapshotPhase;
+pub use common_contracts::house::House;
+use common_contracts::snapshot::MAX_LINK_LENGTH;
+pub use common_contracts::snapshot::{
+    AffectedAccount, ChallengeEvidence, Role, SnapshotMetadata, UserData,
+};
+
+use crate::consts::{MAX_LINKS, MAX_PLATFORM_LENGTH};
+
 #[derive(
     BorshDeserialize,
     BorshSerialize,
@@ -43,19 +53,28 @@ impl Status {
         }
     }
 
-    pub fn event_info(&self) -> (u32, &'static str) {
+    pub fn event_info(&self) -> (u32, SnapshotPhase) {
         match self {
-            Status::Initialization(attempt) => (*attempt, "Initialization"),
-            Status::SnapshotChallenge(attempt) => (*attempt, "SnapshotChallenge"),
-            Status::SnapshotHalted(attempt) => (*attempt, "SnapshotHalted"),
-            Status::Registration(attempt) => (*attempt, "Registration"),
-            Status::RegistrationEnded(attempt) => (*attempt, "RegistrationEnded"),
+            Status::Initialization(attempt) => (*attempt, SnapshotPhase::Initialization),
+            Status::SnapshotChallenge(attempt) => (*attempt, SnapshotPhase::SnapshotChallenge),
+            Status::SnapshotHalted(attempt) => (*attempt, SnapshotPhase::SnapshotHalted),
+            Status::Registration(attempt) => (*attempt, SnapshotPhase::Registration),
+            Status::RegistrationEnded(attempt) => (*attempt, SnapshotPhase::RegistrationEnded),
         }
     }
 }
 
-pub type VoteWeight = u32;
+pub type VoteWeight = u64;
 
+// yoctoNEAR in 1 NEAR
+pub const ONE_NEAR: u128 = 10u128.pow(24);
+// yoctoNEAR in 1 micro NEAR (square root of 1 NEAR in yoctoNEAR)
+pub const ONE_MICRO_NEAR: u128 = 10u128.pow(12);
+
+// Denominator for the tier rates of the piecewise linear formula
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Reward for the activity of the user
 #[derive(
     Clone,
     Copy,
@@ -69,9 +88,207 @@ pub type VoteWeight = u32;
 )]
 #[serde(crate = "near_sdk::serde")]
 #[borsh(crate = "near_sdk::borsh")]
-pub struct VoteWeightConfig {
-    pub threshold_in_nears: u32,
+pub struct ActivityConfig {
     pub activity_reward_in_votes: u32,
+    /// Active months above the cap are not rewarded
+    pub max_active_months: Option<u32>,
+}
+
+impl ActivityConfig {
+    pub fn votes(&self, active_months: u32) -> u64 {
+        let months = self
+            .max_active_months
+            .map_or(active_months, |max| active_months.min(max));
+        u64::from(months) * u64::from(self.activity_reward_in_votes)
+    }
+}
+
+/// Linear segment of the piecewise linear formula
+#[derive(
+    Clone,
+    Copy,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    NearSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct WeightTier {
+    /// Stake where the tier starts. The tier lasts until the start of the next one
+    pub from_in_nears: u32,
+    /// Votes per NEAR in basis points (10_000 = 1 vote per NEAR)
+    pub votes_per_near_bps: u32,
+}
+
+/// Formula to convert the snapshot data into the vote weight.
+/// The stake part depends on the variant, the activity part is the same for every formula.
+#[derive(
+    Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, NearSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VoteWeightConfig {
+    // 1 vote per NEAR
+    Linear {
+        activity: ActivityConfig,
+    },
+    // 1 vote per NEAR up to the cap
+    Capped {
+        cap_in_nears: u32,
+        activity: ActivityConfig,
+    },
+    // 1 vote per NEAR up to the threshold, then n-th root of the stake above the threshold
+    Root {
+        threshold_in_nears: u32,
+        root: u32,
+        activity: ActivityConfig,
+    },
+    // 1 vote per NEAR up to the threshold, then `votes_per_doubling` votes
+    // each time the stake above the threshold doubles
+    Logarithmic {
+        threshold_in_nears: u32,
+        votes_per_doubling: u32,
+        activity: ActivityConfig,
+    },
+    // Sum of the linear tiers sorted by the start of the tier
+    PiecewiseLinear {
+        tiers: Vec<WeightTier>,
+        activity: ActivityConfig,
+    },
+}
+
+impl VoteWeightConfig {
+    pub fn activity(&self) -> &ActivityConfig {
+        match self {
+            VoteWeightConfig::Linear { activity }
+            | VoteWeightConfig::Capped { activity, .. }
+            | VoteWeightConfig::Root { activity, .. }
+            | VoteWeightConfig::Logarithmic { activity, .. }
+            | VoteWeightConfig::PiecewiseLinear { activity, .. } => activity,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            VoteWeightConfig::Linear { .. }
+            | VoteWeightConfig::Capped { .. }
+            | VoteWeightConfig::Logarithmic { .. } => true,
+            VoteWeightConfig::Root { root, .. } => *root > 0,
+            VoteWeightConfig::PiecewiseLinear { tiers, .. } => {
+                tiers.first().map_or(false, |tier| tier.from_in_nears == 0)
+                    && tiers
+                        .windows(2)
+                        .all(|pair| pair[0].from_in_nears < pair[1].from_in_nears)
+            }
+        }
+    }
+
+    /// Stake votes with the yoctoNEAR precision. Only whole votes are counted.
+    pub fn stake_votes(&self, stake: NearToken) -> u64 {
+        let stake = stake.as_yoctonear();
+        let votes = match self {
+            VoteWeightConfig::Linear { .. } => stake / ONE_NEAR,
+            VoteWeightConfig::Capped { cap_in_nears, .. } => {
+                (stake / ONE_NEAR).min(u128::from(*cap_in_nears))
+            }
+            VoteWeightConfig::Root {
+                threshold_in_nears,
+                root,
+                ..
+            } => {
+                let threshold = u128::from(*threshold_in_nears) * ONE_NEAR;
+                if stake <= threshold {
+                    stake / ONE_NEAR
+                } else {
+                    u128::from(*threshold_in_nears) + near_root(stake - threshold, *root)
+                }
+            }
+            VoteWeightConfig::Logarithmic {
+                threshold_in_nears,
+                votes_per_doubling,
+                ..
+            } => {
+                let threshold = u128::from(*threshold_in_nears) * ONE_NEAR;
+                if stake <= threshold {
+                    stake / ONE_NEAR
+                } else {
+                    // floor(log2(excess + 1)), the excess is in NEAR
+                    let doublings = 127 - ((stake - threshold) / ONE_NEAR + 1).leading_zeros();
+                    u128::from(*threshold_in_nears)
+                        + u128::from(doublings) * u128::from(*votes_per_doubling)
+                }
+            }
+            VoteWeightConfig::PiecewiseLinear { tiers, .. } => {
+                // Whole NEARs and the remainders are accumulated separately to avoid overflow
+                let (whole, fraction) = tiers.iter().enumerate().fold(
+                    (0u128, 0u128),
+                    |(whole, fraction), (i, tier)| {
+                        let from = u128::from(tier.from_in_nears) * ONE_NEAR;
+                        let to = tiers.get(i + 1).map_or(stake, |next| {
+                            stake.min(u128::from(next.from_in_nears) * ONE_NEAR)
+                        });
+                        let segment = to.saturating_sub(from);
+                        let rate = u128::from(tier.votes_per_near_bps);
+                        (
+                            whole.saturating_add(segment / ONE_NEAR * rate),
+                            fraction.saturating_add(segment % ONE_NEAR * rate),
+                        )
+                    },
+                );
+                whole.saturating_add(fraction / ONE_NEAR) / BPS_DENOMINATOR
+            }
+        };
+        u64::try_from(votes).unwrap_or(u64::MAX)
+    }
+}
+
+/// Largest integer `r` such that `r^2 <= value`
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method starting from the power of two above the root
+    let mut root = 1u128 << ((128 - value.leading_zeros()) / 2 + 1);
+    loop {
+        let next = (root + value / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// n-th root of the yoctoNEAR amount in whole NEARs: largest integer `r` such that `r^root NEAR <= yocto`
+pub fn near_root(yocto: u128, root: u32) -> u128 {
+    match root {
+        0 | 1 => yocto / ONE_NEAR,
+        // sqrt(yocto / 10^24) = sqrt(yocto) / 10^12
+        2 => isqrt(yocto) / ONE_MICRO_NEAR,
+        _ => {
+            let fits = |candidate: u128| {
+                candidate
+                    .checked_pow(root)
+                    .and_then(|power| power.checked_mul(ONE_NEAR))
+                    .map_or(false, |power| power <= yocto)
+            };
+            let (mut low, mut high) = (0u128, isqrt(yocto / ONE_NEAR));
+            while low < high {
+                // Upper median to always make progress
+                let mid = low + (high - low + 1) / 2;
+                if fits(mid) {
+                    low = mid;
+                } else {
+                    high = mid - 1;
+                }
+            }
+            low
+        }
+    }
 }
 
 #[derive(
@@ -89,37 +306,175 @@ pub struct VoteWeightConfig {
 #[borsh(crate = "near_sdk::borsh")]
 pub struct SnapshotConfig {
     pub challenge_threshold_in_nears: u32,
+    /// Posted by the admin on the start of the challenge.
+    /// Goes to the challengers if the snapshot is halted.
+    pub admin_bond_in_nears: u32,
     pub challenge_timeout_in_millis: u64,
     pub registration_timeout_in_millis: u64,
 }
 
+/// Vote weight of the snapshot data under the config
+pub trait UserVoteWeight {
+    fn vote_weight(&self, config: &VoteWeightConfig) -> VoteWeight;
+}
+
+impl UserVoteWeight for UserData {
+    fn vote_weight(&self, config: &VoteWeightConfig) -> VoteWeight {
+        config
+            .stake_votes(self.stake)
+            .saturating_add(config.activity().votes(self.active_months))
+    }
+}
+
+/// Information that the nominee shares with the voters
 #[derive(
     Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, NearSchema, Debug, PartialEq,
 )]
 #[serde(crate = "near_sdk::serde")]
 #[borsh(crate = "near_sdk::borsh")]
-pub struct UserData {
-    pub active_months: u32,
-    pub stake: NearToken,
+pub struct NomineeProfile {
+    /// Electoral platform of the nominee
+    pub platform: String,
+    /// Name of the house the nominee is running for
+    pub house: String,
+    /// Links to the social profiles, forum posts, etc.
+    pub links: Vec<String>,
+}
+
+impl NomineeProfile {
+    pub fn is_valid(&self) -> bool {
+        self.platform.len() <= MAX_PLATFORM_LENGTH
+            && self.links.len() <= MAX_LINKS
+            && self.links.iter().all(|link| link.len() <= MAX_LINK_LENGTH)
+    }
+}
+
+/// Proof that the user data is a part of the snapshot committed with the snapshot root
+#[derive(Serialize, Deserialize, NearSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SnapshotProof {
+    pub user_data: UserData,
+    /// Sibling hashes from the leaf up to the root
+    pub path: Vec<Base58CryptoHash>,
+}
+
+/// Evidence submitted by the challenger
+#[derive(
+    Serialize, Deserialize, NearSchema, BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct EvidenceRecord {
+    pub challenger: AccountId,
+    pub evidence: ChallengeEvidence,
+    /// In milliseconds
+    pub submitted_at: u64,
+}
+
+/// The reason the attempt was halted
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Clone,
+    Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum HaltReason {
+    // Challengers deposited more than the challenge threshold
+    ChallengeThreshold,
+    // Admin restarted the process during the challenge
+    AdminRestart,
+}
+
+/// The state of the challenger's refund
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    NearSchema,
+    Debug,
+    PartialEq,
+    Clone,
+    Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum RefundState {
+    // The deposit is held by the contract
+    Active,
+    // The refund transfer is scheduled and waits for the callback
+    PendingRefund,
+    // The deposit was returned to the challenger
+    Refunded,
+}
+
+/// History of the snapshot attempt. Timestamps are in milliseconds.
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, NearSchema, Debug, PartialEq, Clone,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct AttemptRecord {
+    /// Merkle root of the snapshot committed at the start of the challenge
+    pub snapshot_root: Option<Base58CryptoHash>,
+    /// Number of the bulk loaded users at the start of the challenge
+    pub total_eligible_users: u32,
+    /// Provenance of the snapshot at the start of the challenge
+    pub metadata: Option<SnapshotMetadata>,
+    pub initialization_started_at: u64,
+    pub challenge_started_at: Option<u64>,
+    pub registration_started_at: Option<u64>,
+    /// End of the registration or the moment the attempt was halted
+    pub ended_at: Option<u64>,
+    pub total_challenged: NearToken,
+    pub total_challengers: u32,
+    pub halt_reason: Option<HaltReason>,
+    /// Admin bond posted on the start of the challenge
+    pub admin_bond: NearToken,
+    /// Index of the first evidence of the attempt in the list of all evidence
+    pub first_evidence: u32,
+    pub total_evidence: u32,
 }
 
-impl UserData {
-    pub fn new(active_months: u32, stake: NearToken) -> Self {
+impl AttemptRecord {
+    pub fn new(initialization_started_at: u64, first_evidence: u32) -> Self {
         Self {
-            active_months,
-            stake,
+            snapshot_root: None,
+            total_eligible_users: 0,
+            metadata: None,
+            initialization_started_at,
+            challenge_started_at: None,
+            registration_started_at: None,
+            ended_at: None,
+            total_challenged: NearToken::from_yoctonear(0),
+            total_challengers: 0,
+            halt_reason: None,
+            admin_bond: NearToken::from_yoctonear(0),
+            first_evidence,
+            total_evidence: 0,
         }
     }
 
-    pub fn vote_weight(&self, config: VoteWeightConfig) -> VoteWeight {
-        let stake = self.stake.as_near() as u32;
-        let stake_votes = if stake <= config.threshold_in_nears {
-            stake
-        } else {
-            f64::from(stake - config.threshold_in_nears).sqrt() as u32 + config.threshold_in_nears
-        };
-        let activity_votes = self.active_months * config.activity_reward_in_votes;
-        stake_votes + activity_votes
+    /// Share of the admin bond for the challenger with the given deposit in this attempt.
+    ///
+    /// The bond is split pro-rata among the challengers once the attempt is halted.
+    pub fn reward(&self, deposit: NearToken) -> NearToken {
+        if self.halt_reason.is_none() || self.total_challenged.is_zero() {
+            return NearToken::from_yoctonear(0);
+        }
+
+        // Calculated with micro NEAR precision to stay within u128
+        let bond = self.admin_bond.as_yoctonear() / ONE_MICRO_NEAR;
+        let deposit = deposit.as_yoctonear() / ONE_MICRO_NEAR;
+        let total = (self.total_challenged.as_yoctonear() / ONE_MICRO_NEAR).max(1);
+        NearToken::from_yoctonear(bond * deposit / total * ONE_MICRO_NEAR)
     }
 }
 
@@ -127,33 +482,386 @@ impl UserData {
 mod test {
     use super::*;
 
+    fn activity(activity_reward_in_votes: u32) -> ActivityConfig {
+        ActivityConfig {
+            activity_reward_in_votes,
+            max_active_months: None,
+        }
+    }
+
+    fn sqrt_config(threshold_in_nears: u32, activity_reward_in_votes: u32) -> VoteWeightConfig {
+        VoteWeightConfig::Root {
+            threshold_in_nears,
+            root: 2,
+            activity: activity(activity_reward_in_votes),
+        }
+    }
+
     #[test]
     fn test_vote_weight() {
         let user = UserData::new(1, NearToken::from_near(1));
-        let vote_config = VoteWeightConfig {
-            threshold_in_nears: 1,
-            activity_reward_in_votes: 1,
-        };
-        assert_eq!(user.vote_weight(vote_config), 2);
+        assert_eq!(user.vote_weight(&sqrt_config(1, 1)), 2);
     }
 
     #[test]
     fn test_threshhold() {
         let user = UserData::new(5, NearToken::from_near(10500));
-        let vote_config = VoteWeightConfig {
-            threshold_in_nears: 500,
-            activity_reward_in_votes: 1,
-        };
-        assert_eq!(user.vote_weight(vote_config), 100 + 500 + 5); // sqrt(10000) + 500 + 5 activity
+        assert_eq!(user.vote_weight(&sqrt_config(500, 1)), 100 + 500 + 5); // sqrt(10000) + 500 + 5 activity
     }
 
     #[test]
     fn test_threshhold_rounding() {
         let user: UserData = UserData::new(5, NearToken::from_near(10600));
-        let vote_config = VoteWeightConfig {
-            threshold_in_nears: 500,
-            activity_reward_in_votes: 3,
+        assert_eq!(user.vote_weight(&sqrt_config(500, 3)), 100 + 500 + 5 * 3); // sqrt(10100) + 500 + 5 activity
+    }
+
+    #[test]
+    fn test_linear() {
+        let config = VoteWeightConfig::Linear {
+            activity: activity(2),
+        };
+        let user = UserData::new(3, NearToken::from_near(10500));
+        assert_eq!(user.vote_weight(&config), 10500 + 3 * 2);
+    }
+
+    #[test]
+    fn test_capped() {
+        let config = VoteWeightConfig::Capped {
+            cap_in_nears: 1000,
+            activity: activity(1),
+        };
+        assert_eq!(
+            UserData::new(1, NearToken::from_near(999)).vote_weight(&config),
+            999 + 1
+        );
+        assert_eq!(
+            UserData::new(1, NearToken::from_near(10500)).vote_weight(&config),
+            1000 + 1
+        );
+    }
+
+    #[test]
+    fn test_cubic_root() {
+        let config = VoteWeightConfig::Root {
+            threshold_in_nears: 100,
+            root: 3,
+            activity: activity(0),
+        };
+        // cbrt(1000) + 100
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(1100)).vote_weight(&config),
+            110
+        );
+        // cbrt(999) rounds down
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(1099)).vote_weight(&config),
+            109
+        );
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(50)).vote_weight(&config),
+            50
+        );
+    }
+
+    #[test]
+    fn test_logarithmic() {
+        let config = VoteWeightConfig::Logarithmic {
+            threshold_in_nears: 100,
+            votes_per_doubling: 10,
+            activity: activity(0),
+        };
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(100)).vote_weight(&config),
+            100
+        );
+        // log2(1 + 1) = 1
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(101)).vote_weight(&config),
+            110
+        );
+        // log2(1023 + 1) = 10
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(1123)).vote_weight(&config),
+            200
+        );
+        // log2(1022 + 1) rounds down to 9
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(1122)).vote_weight(&config),
+            190
+        );
+    }
+
+    #[test]
+    fn test_piecewise_linear() {
+        let config = VoteWeightConfig::PiecewiseLinear {
+            tiers: vec![
+                WeightTier {
+                    from_in_nears: 0,
+                    votes_per_near_bps: 10_000,
+                },
+                WeightTier {
+                    from_in_nears: 100,
+                    votes_per_near_bps: 5_000,
+                },
+                WeightTier {
+                    from_in_nears: 1000,
+                    votes_per_near_bps: 100,
+                },
+            ],
+            activity: activity(0),
+        };
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(50)).vote_weight(&config),
+            50
+        );
+        // 100 + 900 / 2
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(1000)).vote_weight(&config),
+            550
+        );
+        // 100 + 450 + 10000 / 100
+        assert_eq!(
+            UserData::new(0, NearToken::from_near(11000)).vote_weight(&config),
+            650
+        );
+    }
+
+    #[test]
+    fn test_activity_cap() {
+        let config = VoteWeightConfig::Linear {
+            activity: ActivityConfig {
+                activity_reward_in_votes: 10,
+                max_active_months: Some(12),
+            },
+        };
+        assert_eq!(
+            UserData::new(5, NearToken::from_near(0)).vote_weight(&config),
+            50
+        );
+        assert_eq!(
+            UserData::new(36, NearToken::from_near(0)).vote_weight(&config),
+            120
+        );
+    }
+
+    #[test]
+    fn test_overflow_saturates() {
+        let user = UserData::new(u32::MAX, NearToken::from_yoctonear(u128::MAX));
+        let configs = [
+            VoteWeightConfig::Linear {
+                activity: activity(u32::MAX),
+            },
+            VoteWeightConfig::Capped {
+                cap_in_nears: u32::MAX,
+                activity: activity(u32::MAX),
+            },
+            sqrt_config(u32::MAX, u32::MAX),
+            VoteWeightConfig::Logarithmic {
+                threshold_in_nears: 0,
+                votes_per_doubling: u32::MAX,
+                activity: activity(u32::MAX),
+            },
+            VoteWeightConfig::PiecewiseLinear {
+                tiers: vec![WeightTier {
+                    from_in_nears: 0,
+                    votes_per_near_bps: u32::MAX,
+                }],
+                activity: activity(u32::MAX),
+            },
+        ];
+        for config in configs {
+            let activity_votes = u64::from(u32::MAX) * u64::from(u32::MAX);
+            assert!(user.vote_weight(&config) >= activity_votes);
+        }
+
+        let linear = VoteWeightConfig::Linear {
+            activity: activity(u32::MAX),
+        };
+        assert_eq!(user.vote_weight(&linear), VoteWeight::MAX);
+
+        // Stake above u32::MAX NEAR is not truncated anymore
+        let whale = UserData::new(0, NearToken::from_near(u128::from(u32::MAX) + 10));
+        assert_eq!(
+            whale.vote_weight(&sqrt_config(u32::MAX, 0)),
+            u64::from(u32::MAX) + 3
+        );
+    }
+
+    #[test]
+    fn test_sub_near_stake() {
+        let half = NearToken::from_millinear(500);
+        let config = sqrt_config(100, 0);
+        assert_eq!(UserData::new(0, half).vote_weight(&config), 0);
+
+        // 104.5 NEAR: 100 + sqrt(4.5)
+        let stake = NearToken::from_near(104).saturating_add(half);
+        assert_eq!(UserData::new(0, stake).vote_weight(&config), 102);
+
+        let piecewise = VoteWeightConfig::PiecewiseLinear {
+            tiers: vec![WeightTier {
+                from_in_nears: 0,
+                votes_per_near_bps: 20_000,
+            }],
+            activity: activity(0),
+        };
+        // 2.5 NEAR * 2 votes per NEAR
+        let stake = NearToken::from_near(2).saturating_add(half);
+        assert_eq!(UserData::new(0, stake).vote_weight(&piecewise), 5);
+    }
+
+    #[test]
+    fn test_integer_sqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u128::MAX), u128::from(u64::MAX));
+        assert_eq!(isqrt(ONE_NEAR), ONE_MICRO_NEAR);
+    }
+
+    #[test]
+    fn test_near_root() {
+        assert_eq!(near_root(ONE_NEAR * 1000, 3), 10);
+        assert_eq!(near_root(ONE_NEAR * 1000 - 1, 3), 9);
+        assert_eq!(near_root(ONE_NEAR * 16, 4), 2);
+        assert_eq!(near_root(ONE_NEAR * 16 - 1, 4), 1);
+        assert_eq!(near_root(ONE_NEAR - 1, 2), 0);
+        assert_eq!(near_root(u128::MAX, 128), 1);
+        assert_eq!(near_root(u128::MAX, 1), u128::MAX / ONE_NEAR);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(sqrt_config(100, 10).is_valid());
+        assert!(!VoteWeightConfig::Root {
+            threshold_in_nears: 100,
+            root: 0,
+            activity: activity(10),
+        }
+        .is_valid());
+
+        let tier = |from_in_nears| WeightTier {
+            from_in_nears,
+            votes_per_near_bps: 10_000,
+        };
+        let piecewise = |tiers| VoteWeightConfig::PiecewiseLinear {
+            tiers,
+            activity: activity(10),
         };
-        assert_eq!(user.vote_weight(vote_config), 100 + 500 + 5 * 3); // sqrt(10100) + 500 + 5 activity
+        assert!(piecewise(vec![tier(0), tier(100)]).is_valid());
+        assert!(!piecewise(vec![]).is_valid());
+        assert!(!piecewise(vec![tier(10), tier(100)]).is_valid());
+        assert!(!piecewise(vec![tier(0), tier(100), tier(100)]).is_valid());
+    }
+
+    /// Snapshotter `stakePower` + `activityPower` (snapshotter/server/api/snapshot.js)
+    fn snapshotter_config() -> VoteWeightConfig {
+        VoteWeightConfig::Root {
+            threshold_in_nears: 1000,
+            root: 2,
+            activity: activity(20),
+        }
+    }
+
+    /// Port of the snapshotter `stakePower` + `activityPower`.
+    /// JS numbers are exact below 2^53 NEAR, which covers the total NEAR supply.
+    fn snapshotter_vote_power(stake: u128, active_months: u32) -> u64 {
+        let threshold = 1000 * ONE_NEAR;
+        let stake_power = if stake > threshold {
+            1000 + (((stake - threshold) / ONE_NEAR) as f64).sqrt().floor() as u64
+        } else {
+            (stake / ONE_NEAR) as u64
+        };
+        stake_power + u64::from(active_months) * 20
+    }
+
+    #[test]
+    fn test_snapshotter_reference_values() {
+        // (stake in yoctoNEAR, active months, vote power) produced with the snapshotter formula
+        let reference = [
+            ("0", 0, 0),
+            ("1", 0, 0),
+            ("999999999999999999999999", 1, 20),
+            ("1000000000000000000000000", 1, 21),
+            ("1500000000000000000000000", 3, 61),
+            ("999999999999999999999999999", 12, 1239),
+            ("1000000000000000000000000000", 24, 1480),
+            ("1000000000000000000000000001", 2, 1040),
+            ("1000999999999999999999999999", 5, 1100),
+            ("1001000000000000000000000000", 5, 1101),
+            ("1003999999999999999999999999", 7, 1141),
+            ("1004000000000000000000000000", 0, 1002),
+            ("11000000000000000000000000000", 60, 2300),
+            ("11099000000000000000000000000", 13, 1360),
+            ("123456789123456789123456789012", 8, 1509),
+            ("1000000000000000000000000000000000", 36, 33342),
+            ("77777777777777777777777777777777", 48, 10779),
+        ];
+
+        for (stake, active_months, expected) in reference {
+            let stake = NearToken::from_yoctonear(stake.parse().unwrap());
+            let user = UserData::new(active_months, stake);
+            assert_eq!(user.vote_weight(&snapshotter_config()), expected);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_matches_snapshotter(
+            // Up to 10 billion NEAR
+            stake in 0u128..10_000_000_000 * ONE_NEAR,
+            active_months in 0u32..1000,
+        ) {
+            let user = UserData::new(active_months, NearToken::from_yoctonear(stake));
+            proptest::prop_assert_eq!(
+                user.vote_weight(&snapshotter_config()),
+                snapshotter_vote_power(stake, active_months)
+            );
+        }
+
+        #[test]
+        fn prop_matches_snapshotter_near_squares(
+            root in 0u128..100_000,
+            offset in 0u128..2 * ONE_NEAR,
+        ) {
+            // Stakes around the perfect squares are the most sensitive to rounding
+            let stake = 1000 * ONE_NEAR + (root * root * ONE_NEAR).saturating_sub(offset);
+            let user = UserData::new(0, NearToken::from_yoctonear(stake));
+            proptest::prop_assert_eq!(
+                user.vote_weight(&snapshotter_config()),
+                snapshotter_vote_power(stake, 0)
+            );
+        }
+
+        #[test]
+        fn prop_isqrt(value in proptest::num::u128::ANY) {
+            let root = isqrt(value);
+            proptest::prop_assert!(root * root <= value);
+            proptest::prop_assert!((root + 1).checked_mul(root + 1).map_or(true, |next| next > value));
+        }
+
+        #[test]
+        fn prop_near_root(yocto in proptest::num::u128::ANY, root in 2u32..10) {
+            let result = near_root(yocto, root);
+            let power = |r: u128| r.checked_pow(root).and_then(|p| p.checked_mul(ONE_NEAR));
+            proptest::prop_assert!(power(result).map_or(false, |p| p <= yocto));
+            proptest::prop_assert!(power(result + 1).map_or(true, |p| p > yocto));
+        }
+
+        #[test]
+        fn prop_weight_is_monotonic(
+            stake in proptest::num::u128::ANY,
+            extra in 0u128..1000 * ONE_NEAR,
+            root in 1u32..5,
+        ) {
+            let config = VoteWeightConfig::Root {
+                threshold_in_nears: 1000,
+                root,
+                activity: activity(20),
+            };
+            let less = UserData::new(1, NearToken::from_yoctonear(stake));
+            let more = UserData::new(1, NearToken::from_yoctonear(stake.saturating_add(extra)));
+            proptest::prop_assert!(less.vote_weight(&config) <= more.vote_weight(&config));
+        }
     }
 }
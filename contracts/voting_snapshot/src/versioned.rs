@@ -0,0 +1,115 @@
+//! Versions of the types stored in the contract state.
+//!
+//! Each enum dereferences to the latest version of the type.
+//! Values of the older versions are converted to the latest one in `migrate`.
+
+use std::ops::{Deref, DerefMut};
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::types::{SnapshotConfig, Status, UserData, VoteWeightConfig};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone, Copy)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedStatus {
+    V1(Status),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedSnapshotConfig {
+    V1(SnapshotConfig),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedVoteWeightConfig {
+    V1(VoteWeightConfig),
+}
+
+/// Snapshot data of the eligible voter
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedUserData {
+    V1(UserData),
+}
+
+impl From<Status> for VersionedStatus {
+    fn from(status: Status) -> Self {
+        Self::V1(status)
+    }
+}
+
+impl Deref for VersionedStatus {
+    type Target = Status;
+
+    fn deref(&self) -> &Status {
+        match self {
+            Self::V1(status) => status,
+        }
+    }
+}
+
+impl DerefMut for VersionedStatus {
+    fn deref_mut(&mut self) -> &mut Status {
+        match self {
+            Self::V1(status) => status,
+        }
+    }
+}
+
+impl From<SnapshotConfig> for VersionedSnapshotConfig {
+    fn from(config: SnapshotConfig) -> Self {
+        Self::V1(config)
+    }
+}
+
+impl Deref for VersionedSnapshotConfig {
+    type Target = SnapshotConfig;
+
+    fn deref(&self) -> &SnapshotConfig {
+        match self {
+            Self::V1(config) => config,
+        }
+    }
+}
+
+impl From<VoteWeightConfig> for VersionedVoteWeightConfig {
+    fn from(config: VoteWeightConfig) -> Self {
+        Self::V1(config)
+    }
+}
+
+impl Deref for VersionedVoteWeightConfig {
+    type Target = VoteWeightConfig;
+
+    fn deref(&self) -> &VoteWeightConfig {
+        match self {
+            Self::V1(config) => config,
+        }
+    }
+}
+
+impl From<UserData> for VersionedUserData {
+    fn from(user_data: UserData) -> Self {
+        Self::V1(user_data)
+    }
+}
+
+impl Deref for VersionedUserData {
+    type Target = UserData;
+
+    fn deref(&self) -> &UserData {
+        match self {
+            Self::V1(user_data) => user_data,
+        }
+    }
+}
+
+impl DerefMut for VersionedUserData {
+    fn deref_mut(&mut self) -> &mut UserData {
+        match self {
+            Self::V1(user_data) => user_data,
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use near_sdk::{
+    json_types::Base58CryptoHash,
     serde::{Deserialize, Serialize},
     NearSchema,
 };
 
-use crate::{types::VoteWeight, *};
+use crate::{
+    types::{UserVoteWeight, VoteWeight},
+    *,
+};
 
 #[derive(NearSchema, Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
@@ -16,12 +20,27 @@ pub struct VoterInformation {
 impl Contract {
     /// *View*: Returns the vote weight configuration
     pub fn get_vote_config(&self) -> VoteWeightConfig {
-        self.vote_config
+        (*self.vote_config).clone()
     }
 
     /// *View*: Returns the snapshot configuration (Time for challenge, registration, threshold for challenge)
     pub fn get_process_config(&self) -> SnapshotConfig {
-        self.process_config
+        *self.process_config
+    }
+
+    /// *View*: Returns the houses that nominees register into
+    pub fn get_houses(&self) -> Vec<House> {
+        self.houses.clone()
+    }
+
+    /// *View*: Returns the Merkle root of the full snapshot if it was committed
+    pub fn get_snapshot_root(&self) -> Option<Base58CryptoHash> {
+        self.snapshot_root.map(Into::into)
+    }
+
+    /// *View*: Returns the provenance of the loaded snapshot if it was set
+    pub fn get_snapshot_metadata(&self) -> Option<SnapshotMetadata> {
+        self.snapshot_metadata.clone()
     }
 
     /// *View*: Returns the end time of the current phase in milliseconds.
@@ -31,9 +50,21 @@ impl Contract {
         self.end_time_in_millis
     }
 
+    /// *View*: Returns if the state-changing methods are paused
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// *View*: Returns the time in milliseconds when the contract was paused
+    ///
+    /// The end time is shifted by the pause duration on unpause
+    pub fn get_paused_at(&self) -> Option<u64> {
+        self.paused_at
+    }
+
     /// *View*: Returns the current phase of the snapshot
     pub fn get_status(&self) -> Status {
-        self.status
+        *self.status
     }
 
     /// *View*: Return the total amount of NEAR tokens challenged in the current iteration
@@ -41,6 +72,42 @@ impl Contract {
         self.total_challenged
     }
 
+    /// *View*: Returns the history of the given attempt
+    pub fn get_attempt(&self, attempt: u32) -> Option<AttemptRecord> {
+        self.attempts.get(attempt).cloned()
+    }
+
+    /// *View*: Returns the history of all attempts starting from the first one
+    pub fn get_attempts(&self) -> Vec<AttemptRecord> {
+        self.attempts.iter().cloned().collect()
+    }
+
+    /// *View*: Returns the evidence submitted by the challengers in the given attempt
+    pub fn get_challenge_evidence(
+        &self,
+        attempt: u32,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<EvidenceRecord> {
+        let record = match self.attempts.get(attempt) {
+            Some(record) => record,
+            None => return vec![],
+        };
+
+        (from_index..record.total_evidence)
+            .take(limit as usize)
+            .filter_map(|i| self.challenge_evidence.get(record.first_evidence + i))
+            .cloned()
+            .collect()
+    }
+
+    /// *View*: Returns the amount the challenger deposited in the given attempt
+    ///
+    /// Unlike `get_individual_challenge`, it's kept after the deposit is refunded
+    pub fn get_attempt_challenge(&self, attempt: u32, challenger: AccountId) -> Option<NearToken> {
+        self.attempt_challenges.get(&(attempt, challenger)).cloned()
+    }
+
     /// *View*: Returns the individual challenge amount for a given challenger
     ///
     /// Returns None if the challenger has not challenged or the challenger already withdrew the deposit
@@ -48,15 +115,64 @@ impl Contract {
         self.challengers.get(challenger).cloned()
     }
 
+    /// *View*: Returns the refund state of a given challenger
+    ///
+    /// Returns None if the user has never challenged the snapshot
+    pub fn get_refund_state(&self, challenger: &AccountId) -> Option<RefundState> {
+        self.refund_states.get(challenger).copied()
+    }
+
+    /// *View*: Returns the unclaimed share of the admin bond for a given challenger
+    ///
+    /// The reward is paid together with the deposit in `refund_bond`
+    pub fn get_challenger_reward(&self, challenger: &AccountId) -> NearToken {
+        self.unclaimed_rewards(challenger).0
+    }
+
+    /// *View*: Returns the challengers that are not refunded yet with their deposits.
+    /// Refunded challenger is replaced with the last one in the list.
+    pub fn get_challengers(&self, from_index: u32, limit: u32) -> Vec<(AccountId, NearToken)> {
+        self.challengers
+            .page(from_index, limit)
+            .map(|(challenger, deposit)| (challenger.clone(), *deposit))
+            .collect()
+    }
+
     /// *View*: Returns admin account ID
     pub fn get_admin(&self) -> AccountId {
         self.admin.clone()
     }
 
+    /// *View*: Returns the account proposed as the new admin
+    pub fn get_pending_admin(&self) -> Option<AccountId> {
+        self.pending_admin.clone()
+    }
+
+    /// *View*: Returns the roles of the given account. The admin has the owner role.
+    pub fn get_roles(&self, account_id: &AccountId) -> Vec<Role> {
+        let mut roles = if account_id == &self.admin {
+            vec![Role::Owner]
+        } else {
+            Vec::new()
+        };
+        if let Some(granted) = self.roles.get(account_id) {
+            roles.extend(granted);
+        }
+        roles
+    }
+
+    /// *View*: Returns the accounts with the granted roles in the order of the first grant
+    pub fn get_role_members(&self, from_index: u32, limit: u32) -> Vec<(AccountId, Vec<Role>)> {
+        self.roles
+            .page(from_index, limit)
+            .map(|(account_id, roles)| (account_id.clone(), roles.clone()))
+            .collect()
+    }
+
     /// *View*: Returns the vote power of a individual voter
     pub fn get_vote_power(&self, voter: &AccountId) -> Option<VoteWeight> {
         let voter_info = self.eligible_voters.get(voter)?;
-        Some(voter_info.vote_weight(self.vote_config))
+        Some(voter_info.vote_weight(&self.vote_config))
     }
 
     /// *View*: Returns if the given account ID submitted public key and became a voter
@@ -66,7 +182,32 @@ impl Contract {
 
     /// *View*: Returns if the given account ID is a nominee
     pub fn is_nominee(&self, nominee: &AccountId) -> bool {
-        self.nominees.contains(nominee)
+        self.nominees.contains_key(nominee)
+    }
+
+    /// *View*: Returns the profile of the nominee
+    pub fn get_nominee_profile(&self, nominee: &AccountId) -> Option<NomineeProfile> {
+        self.nominees.get(nominee).cloned()
+    }
+
+    /// *View*: Returns the nominees with their profiles in the order of registration.
+    /// Withdrawn nominee is replaced with the last registered one.
+    pub fn get_nominees(&self, from_index: u32, limit: u32) -> Vec<(AccountId, NomineeProfile)> {
+        self.nominees
+            .page(from_index, limit)
+            .map(|(nominee, profile)| (nominee.clone(), profile.clone()))
+            .collect()
+    }
+
+    /// *View*: Returns the house of each nominee from the list. Non-nominees are skipped.
+    pub fn get_nominees_info(&self, nominees: Vec<AccountId>) -> Vec<(AccountId, String)> {
+        nominees
+            .into_iter()
+            .filter_map(|nominee| {
+                let house = self.nominees.get(&nominee)?.house.clone();
+                Some((nominee, house))
+            })
+            .collect()
     }
 
     /// *View*: Returns if the given account ID is able to become a voter or a nominee
@@ -92,6 +233,19 @@ impl Contract {
             .collect()
     }
 
+    /// *View*: Returns vote weight and public key of the voters in the order of registration
+    pub fn get_voters(&self, from_index: u32, limit: u32) -> Vec<(AccountId, VoterInformation)> {
+        self.voters_index
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|voter| {
+                self.get_voter_information(voter)
+                    .map(|info| (voter.clone(), info))
+            })
+            .collect()
+    }
+
     /// *View*: Returns amount of eligible users to become voters
     pub fn get_total_eligible_users(&self) -> u32 {
         self.total_eligible_users
@@ -102,9 +256,16 @@ impl Contract {
         self.total_voters
     }
 
+    /// *View*: Shows total number of nominees that are registered
+    pub fn get_total_nominees(&self) -> u32 {
+        self.nominees.len()
+    }
+
     /// *View*: displays information about snapshot data for particular account
     pub fn get_eligible_voter_info(&self, account_id: &AccountId) -> Option<UserData> {
-        self.eligible_voters.get(account_id).cloned()
+        self.eligible_voters
+            .get(account_id)
+            .map(|user_data| (**user_data).clone())
     }
 }
 
@@ -155,7 +316,7 @@ mod tests {
 
         assert!(contract.get_voter_information(&acc(1)).is_none());
 
-        contract.register_as_voter();
+        contract.register_as_voter(None);
 
         assert_eq!(contract.get_total_voters(), 1);
 
@@ -169,6 +330,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn user_can_list_voters_and_nominees() {
+        let (mut context, mut contract) = setup_ctr();
+
+        context.predecessor_account_id = admin();
+        context.attached_deposit = NearToken::from_millinear(10);
+        testing_env!(context.clone());
+        contract.bulk_load_voters(load_voters());
+
+        move_to_challenge(&mut context, &mut contract);
+        move_to_registration(&mut context, &mut contract);
+
+        for i in [3, 1, 4] {
+            context.predecessor_account_id = acc(i);
+            testing_env!(context.clone());
+            contract.register_as_voter_with_pubkey(pk(), None);
+        }
+        contract.register_as_nominee(profile(), None);
+
+        let voters: Vec<_> = contract
+            .get_voters(0, 10)
+            .into_iter()
+            .map(|(voter, _)| voter)
+            .collect();
+        assert_eq!(voters, vec![acc(3), acc(1), acc(4)]);
+
+        let page = contract.get_voters(1, 1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].0, acc(1));
+        assert_eq!(page[0].1.vote_weight, 11);
+
+        assert!(contract.get_voters(3, 10).is_empty());
+        assert_eq!(
+            contract.get_nominees_info(vec![acc(1), acc(2), acc(4)]),
+            vec![(acc(4), profile().house)]
+        );
+        assert_eq!(contract.get_total_voters(), 3);
+        assert_eq!(contract.get_total_nominees(), 1);
+    }
+
     #[test]
     fn user_can_get_deposit() {
         let (mut context, mut contract) = setup_ctr();
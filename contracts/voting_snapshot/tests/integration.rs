@@ -1,18 +1,35 @@
+use common_contracts::events::{parse_event_log, snapshot::SnapshotEvent};
 use near_sdk::serde_json;
 use near_sdk::serde_json::json;
 use near_sdk::NearToken;
 use near_workspaces::{network::Sandbox, Account, AccountId, Contract, DevNetwork, Worker};
-use voting_snapshot::types::{SnapshotConfig, Status, UserData, VoteWeightConfig};
+use voting_snapshot::types::{
+    ActivityConfig, SnapshotConfig, SnapshotMetadata, Status, UserData, VoteWeightConfig,
+};
 
 pub fn default_vote_config() -> VoteWeightConfig {
-    VoteWeightConfig {
+    VoteWeightConfig::Root {
         threshold_in_nears: 40,
-        activity_reward_in_votes: 10,
+        root: 2,
+        activity: ActivityConfig {
+            activity_reward_in_votes: 10,
+            max_active_months: None,
+        },
+    }
+}
+pub fn snapshot_metadata() -> SnapshotMetadata {
+    SnapshotMetadata {
+        block_height: 108194270,
+        ipfs_cid: "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string(),
+        data_hash: [1; 32].into(),
+        formula_version: 1,
     }
 }
+
 pub fn default_snapshot_config() -> SnapshotConfig {
     SnapshotConfig {
         challenge_threshold_in_nears: 30,
+        admin_bond_in_nears: 5,
 
         // 5 seconds
         challenge_timeout_in_millis: 5 * 1000,
@@ -95,9 +112,25 @@ impl Ctx {
     }
 
     pub async fn move_to_challenge(&self) -> anyhow::Result<()> {
+        let res = self
+            .admin
+            .call(self.contract.id(), "set_snapshot_metadata")
+            .args_json(json!({ "metadata": snapshot_metadata() }))
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(
+            res.is_success(),
+            "Failed to set snapshot metadata: {:?}",
+            res
+        );
+
         let res = self
             .admin
             .call(self.contract.id(), "start_challenge")
+            .deposit(NearToken::from_near(
+                default_snapshot_config().admin_bond_in_nears.into(),
+            ))
             .max_gas()
             .transact()
             .await?;
@@ -121,6 +154,16 @@ impl Ctx {
             "Failed to challenge: {:?}",
             res
         );
+        if expectation {
+            assert!(
+                res.logs().iter().any(|log| matches!(
+                    parse_event_log(log),
+                    Some(SnapshotEvent::SnapshotChallenged { amount: deposit, .. }) if deposit == amount
+                )),
+                "No snapshot_challenged event: {:?}",
+                res.logs()
+            );
+        }
 
         Ok(())
     }
@@ -207,8 +250,12 @@ async fn halt_and_return_flow() {
 
     ctx.refund_bond().await.unwrap();
 
+    // The only challenger gets the whole admin bond
     let balance_after_refund = ctx.account.view_account().await.unwrap().balance;
-    assert_eq!(balance_before.as_near() - 1, balance_after_refund.as_near());
+    assert_eq!(
+        balance_before.as_near() + u128::from(default_snapshot_config().admin_bond_in_nears) - 1,
+        balance_after_refund.as_near()
+    );
 
     assert_eq!(
         ctx.get_challenge_deposit(ctx.account.id().clone())
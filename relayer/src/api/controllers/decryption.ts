@@ -1,7 +1,7 @@
 import { Request, Response } from "express";
 import { getSecretKeys } from "../utils/secret";
 import { base_encode } from "near-api-js/lib/utils/serialize";
-import { getVoterPublicKey, isNominee, getAllVotes, sendResultsToContract } from "../utils/near";
+import { getVoterPublicKey, getNomineeHouse, getAllVotes, sendResultsToContract } from "../utils/near";
 import { decrypt, verifySignature } from "../../cryptography";
 import { VotingPackage } from "../../cryptography/types";
 
@@ -34,7 +34,7 @@ export const postDecryption = async () => {
         throw new Error(`No votes to decrypt`);
     }
 
-    const decryptedVotes = new Map<string, VotingPackage>();
+    const decryptedVotes = new Map<string, HouseVotes>();
     for (let i = 0; i < encryptedVotes.length; i++) {
         const vote = encryptedVotes[i];
         let result = await decrypt(vote, secretKeys.data.private);
@@ -44,65 +44,79 @@ export const postDecryption = async () => {
             continue;
         }
 
-        let isValid = await validateVote(result.data, i);
-        if (!isValid) {
+        let houseVotes = await validateVote(result.data, i);
+        if (!houseVotes) {
             continue;
         }
 
         if (decryptedVotes.has(result.data.accountId)) {
             console.log(`Found new vote for ${result.data.accountId}. Replacing the old vote`);
         }
-        decryptedVotes.set(result.data.accountId, result.data);
+        decryptedVotes.set(result.data.accountId, houseVotes);
     }
 
-    const results = new Map<string, number>();
-    decryptedVotes.forEach((vote) => {
-        vote.votes.forEach((v) => {
-            results.set(v.candidate, (results.get(v.candidate) ?? 0) + v.weight);
+    // house -> candidate -> weight
+    const results = new Map<string, Map<string, number>>();
+    decryptedVotes.forEach((houseVotes) => {
+        houseVotes.forEach((votes, house) => {
+            const houseResults = results.get(house) ?? new Map<string, number>();
+            votes.forEach((v) => {
+                houseResults.set(v.candidate, (houseResults.get(v.candidate) ?? 0) + v.weight);
+            });
+            results.set(house, houseResults);
         });
     });
 
-    if (!await sendResultsToContract(Array.from(results.entries()))) {
+    const houseResults = Array.from(results.entries())
+        .map(([house, weights]): [string, [string, number][]] => [house, Array.from(weights.entries())]);
+    if (!await sendResultsToContract(houseResults)) {
         throw new Error("Error while submitting results to the contract");
     }
 }
 
-const validateVote = async (vote: VotingPackage, voteNumber: number): Promise<boolean> => {
+// Votes of the voter grouped by the house of the candidate
+type HouseVotes = Map<string, VotingPackage["votes"]>;
+
+/// Validates the vote and groups it by houses.
+/// The voter can spend the whole vote weight in each house.
+const validateVote = async (vote: VotingPackage, voteNumber: number): Promise<HouseVotes | undefined> => {
     const { accountId, votes, signature } = vote;
 
     const voterInfo = await getVoterPublicKey(accountId);
     if (!voterInfo) {
         console.log(`Discard vote ${voteNumber}: Voter is not registered`);
-        return false;
+        return undefined;
     }
 
     const data = base_encode(JSON.stringify({ accountId, votes }));
     if (!verifySignature(data, voterInfo.public_key, signature)) {
         console.log(`Discard vote ${voteNumber}: Invalid user signature`);
-        return false;
+        return undefined;
     }
 
-
-    let totalWeightUsed = 0;
+    const houseVotes: HouseVotes = new Map();
+    const weightUsed = new Map<string, number>();
     for (let i = 0; i < votes.length; i++) {
         let vote = votes[i];
         if (vote.weight < 0) {
             console.log(`Discard vote ${voteNumber}: Invalid vote weight`);
-            return false;
+            return undefined;
         }
-        totalWeightUsed += vote.weight;
 
-        let status = await isNominee(vote.candidate);
-        if (!status) {
+        let house = await getNomineeHouse(vote.candidate);
+        if (!house) {
             console.log(`Discard vote ${voteNumber}: Invalid candidate`);
-            return false;
+            return undefined;
         }
-    }
 
-    if (totalWeightUsed > voterInfo.vote_weight) {
-        console.log(`Discard vote ${voteNumber}: Vote weight exceeds the voter's total weight`);
-        return false;
+        const used = (weightUsed.get(house) ?? 0) + vote.weight;
+        if (used > voterInfo.vote_weight) {
+            console.log(`Discard vote ${voteNumber}: Vote weight in ${house} exceeds the voter's total weight`);
+            return undefined;
+        }
+        weightUsed.set(house, used);
+        houseVotes.set(house, [...(houseVotes.get(house) ?? []), vote]);
     }
 
-    return true;
+    return houseVotes;
 }
@@ -46,6 +46,7 @@ let votingContract: VotingContract;
 type SnapshotContract = Contract & {
     get_voter_information: (args: { voter: AccountId }) => Promise<VoterInfo>;
     is_nominee: (args: { nominee: AccountId }) => Promise<boolean>;
+    get_nominee_profile: (args: { nominee: AccountId }) => Promise<NomineeProfile | null>;
 };
 
 type VotingContract = Contract & {
@@ -53,6 +54,7 @@ type VotingContract = Contract & {
     sumbit_results: (args: any) => Promise<void>;
 
     get_total_votes: () => Promise<number>;
+    get_results_config: () => Promise<ResultsConfig>;
     get_votes: (args: { page: number, limit: number }) => Promise<any>;
 };
 
@@ -63,13 +65,13 @@ export const initializeNear = async () => {
         relayer = await near.account(RELAYER_ACCOUNT!);
 
         snapshotContract = new Contract(relayer, SNAPSHOT_CONTRACT!, {
-            viewMethods: ["get_voter_information", "is_nominee"],
+            viewMethods: ["get_voter_information", "is_nominee", "get_nominee_profile"],
             changeMethods: [],
             useLocalViewExecution: false,
         }) as SnapshotContract;
 
         votingContract = new Contract(relayer, VOTING_CONTRACT!, {
-            viewMethods: ['get_total_votes', 'get_votes'],
+            viewMethods: ['get_total_votes', 'get_votes', 'get_results_config'],
             changeMethods: ['send_encrypted_votes', 'sumbit_results'],
             useLocalViewExecution: false,
         }) as VotingContract;
@@ -84,6 +86,18 @@ export type VoterInfo = {
     public_key: string;
 }
 
+export type ResultsConfig = {
+    bond: string;
+    dispute_deposit: string;
+    challenge_timeout_in_ms: number;
+}
+
+export type NomineeProfile = {
+    platform: string;
+    house: string;
+    links: string[];
+}
+
 // Function to fetch the user's public key from the snapshot contract
 export const getVoterPublicKey = async (accountId: AccountId): Promise<VoterInfo | undefined> => {
     try {
@@ -112,9 +126,12 @@ export const sendVoteToContract = async (encryptedVotingPackage: EncryptedVoting
     }
 };
 
-export const sendResultsToContract = async (results: [AccountId, number][]): Promise<boolean> => {
+// Submits the results of all houses at once. The bond is attached on top of the storage deposit.
+export const sendResultsToContract = async (results: [string, [AccountId, number][]][]): Promise<boolean> => {
     try {
-        await votingContract.sumbit_results({ args: { results }, gas: GAS, amount: DEPOSIT });
+        const { bond } = await votingContract.get_results_config();
+        const amount = (BigInt(bond) + BigInt(DEPOSIT!)).toString();
+        await votingContract.sumbit_results({ args: { results }, gas: GAS, amount });
         return true;
     } catch (error) {
         console.error('Error submitting results to contract:', error);
@@ -177,3 +194,13 @@ export const isNominee = async (accountId: AccountId): Promise<boolean> => {
         return false;
     }
 }
+
+// Returns the house the nominee is running for or undefined if the account is not a nominee
+export const getNomineeHouse = async (accountId: AccountId): Promise<string | undefined> => {
+    try {
+        const profile = await snapshotContract.get_nominee_profile({ nominee: accountId });
+        return profile?.house;
+    } catch (_) {
+        return undefined;
+    }
+}
@@ -1,11 +1,13 @@
 import fs from 'fs';
+import crypto from 'crypto';
 import { program } from 'commander';
-import { keyStores, connect, transactions, Contract } from 'near-api-js';
+import { keyStores, connect, transactions, Contract, utils } from 'near-api-js';
 import { parseNearAmount } from 'near-api-js/lib/utils/format.js';
 import os from 'os';
 import path from 'path';
 import { exit } from 'process';
 import { BN } from 'bn.js';
+import { merkleTree } from './merkle.js';
 
 program
     .description('Load the snapshot data on the contract during the initialization phase.')
@@ -13,7 +15,10 @@ program
     .option('--json <type>', 'Path to the json snapshot', process.env.JSON_PATH)
     .option('--network <type>', 'Testnet or Mainnet', process.env.NETWORK)
     .option('--account <type>', 'Account from keystore to use', process.env.ACCOUNT)
-    .option('--start <type>', 'Start loading from butch X', 0);
+    .option('--start <type>', 'Start loading from butch X', 0)
+    .option('--cid <type>', 'IPFS CID of the published snapshot', process.env.IPFS_CID)
+    .option('--formula-version <type>', 'Version of the vote weight formula', process.env.FORMULA_VERSION)
+    .option('--commit-root', 'Commit the Merkle root of the snapshot, so users that are not loaded can register with the proof');
 
 program.parse(process.argv);
 const options = program.opts();
@@ -28,6 +33,13 @@ let jsonPath = options.json;
 let network = options.network;
 let accountId = options.account;
 let index = options.start;
+let ipfsCid = options.cid;
+let formulaVersion = options.formulaVersion;
+
+if (!ipfsCid || formulaVersion === undefined) {
+    console.error("IPFS CID and formula version are required to set the snapshot metadata");
+    exit(1);
+}
 
 const snapshotToContractRecord = (snapshotRecord) => ([snapshotRecord.account_id, {
     active_months: snapshotRecord.active_months,
@@ -44,7 +56,15 @@ function chunkArray(array, chunkSize, mapper) {
     return chunks;
 }
 
-const snapshot = JSON.parse(fs.readFileSync(jsonPath, 'utf-8')).data;
+const snapshotFile = fs.readFileSync(jsonPath);
+const snapshotJson = JSON.parse(snapshotFile.toString('utf-8'));
+const snapshot = snapshotJson.data;
+const metadata = {
+    block_height: Number(snapshotJson.block_id),
+    ipfs_cid: ipfsCid,
+    data_hash: utils.serialize.base_encode(crypto.createHash('sha256').update(snapshotFile).digest()),
+    formula_version: Number(formulaVersion),
+};
 const transactionsChunks = chunkArray(snapshot, 500, snapshotToContractRecord);
 
 // Load credentials;
@@ -79,7 +99,7 @@ const nearConnection = await connect(connectionConfig);
 
 const account = await nearConnection.account(accountId);
 const contract = new Contract(account, contractId, {
-    changeMethods: ['bulk_load_voters'],
+    changeMethods: ['bulk_load_voters', 'set_snapshot_metadata', 'set_snapshot_root'],
     viewMethods: ['get_status', 'get_total_eligible_users']
 });
 
@@ -110,3 +130,13 @@ const total_in_snapshot = snapshot.length;
 
 console.log(`In contract: ${total_on_contract}`);
 console.log(`In snapshot: ${total_in_snapshot}`);
+
+// Challengers use the metadata to find and verify the loaded dataset
+await contract.set_snapshot_metadata({ metadata }, GAS);
+console.log("Snapshot metadata is set:", metadata);
+
+if (options.commitRoot) {
+    const { root } = merkleTree(snapshot.map(snapshotToContractRecord));
+    await contract.set_snapshot_root({ root }, GAS);
+    console.log("Snapshot root is set:", root);
+}
@@ -0,0 +1,94 @@
+import crypto from 'crypto';
+
+// Domain separation prefixes to make leaves and internal nodes distinguishable.
+// Should match `contracts/voting_snapshot/src/merkle.rs`
+const LEAF_PREFIX = 0;
+const NODE_PREFIX = 1;
+
+const BASE58_ALPHABET = '123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz';
+
+// Same as `Base58CryptoHash` in the contract. Kept here, so the module has no dependencies
+const base58 = (bytes) => {
+    let value = BigInt('0x' + (bytes.toString('hex') || '0'));
+    let encoded = '';
+    while (value > 0n) {
+        encoded = BASE58_ALPHABET[Number(value % 58n)] + encoded;
+        value /= 58n;
+    }
+    for (const byte of bytes) {
+        if (byte !== 0) {
+            break;
+        }
+        encoded = '1' + encoded;
+    }
+    return encoded;
+}
+
+const sha256 = (...parts) => {
+    const hash = crypto.createHash('sha256');
+    parts.forEach((part) => hash.update(part));
+    return hash.digest();
+}
+
+const borshU32 = (value) => {
+    const buffer = Buffer.alloc(4);
+    buffer.writeUInt32LE(value);
+    return buffer;
+}
+
+const borshU128 = (value) => {
+    const buffer = Buffer.alloc(16);
+    const big = BigInt(value);
+    buffer.writeBigUInt64LE(big & 0xffffffffffffffffn, 0);
+    buffer.writeBigUInt64LE(big >> 64n, 8);
+    return buffer;
+}
+
+const borshString = (value) => {
+    const bytes = Buffer.from(value, 'utf-8');
+    return Buffer.concat([borshU32(bytes.length), bytes]);
+}
+
+// sha256(0x00 || borsh(account_id) || borsh(user_data)), `stake` is in yoctoNEAR
+export const leafHash = (accountId, { active_months, stake }) => sha256(
+    Buffer.from([LEAF_PREFIX]),
+    borshString(accountId),
+    borshU32(active_months),
+    borshU128(stake),
+);
+
+// sha256(0x01 || min(a, b) || max(a, b))
+export const nodeHash = (a, b) => {
+    const [left, right] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+    return sha256(Buffer.from([NODE_PREFIX]), left, right);
+}
+
+// Builds the tree over the records `[account_id, user_data]` in the given order.
+// The odd node on the level is promoted to the next level as is.
+// Returns the bs58 root and the bs58 sibling path from the leaf up to the root for each record.
+export const merkleTree = (records) => {
+    let level = records.map(([accountId, userData]) => leafHash(accountId, userData));
+    let positions = records.map((_, i) => i);
+    const proofs = records.map(() => []);
+
+    while (level.length > 1) {
+        positions = positions.map((position, i) => {
+            const sibling = position ^ 1;
+            if (sibling < level.length) {
+                proofs[i].push(base58(level[sibling]));
+            }
+            return Math.floor(position / 2);
+        });
+
+        const next = [];
+        for (let i = 0; i < level.length; i += 2) {
+            next.push(i + 1 < level.length ? nodeHash(level[i], level[i + 1]) : level[i]);
+        }
+        level = next;
+    }
+
+    return {
+        root: level.length ? base58(level[0]) : null,
+        proofs,
+    };
+}
@@ -0,0 +1,26 @@
+import { test } from 'node:test';
+import assert from 'assert';
+import { leafHash, merkleTree } from './merkle.js';
+
+// Same vector is checked in `contracts/voting_snapshot/src/merkle.rs`
+const records = [
+    ['alice.near', { active_months: 3, stake: '1000000000000000000000000' }],
+    ['bob.near', { active_months: 0, stake: '0' }],
+    ['carol.near', { active_months: 12, stake: '340282366920938463463374607431768211455' }],
+];
+
+test('matches the contract test vector', () => {
+    const { root, proofs } = merkleTree(records);
+
+    assert.strictEqual(root, 'x2R9eueSBQare6tLxDFMSFkVWH5XyNQz8ndFJ5kEQpv');
+    assert.deepStrictEqual(proofs[2], ['3bYRYRhzxTnpkTGhGHZTwUHV3oJTMXfMNBoo62bS4ULr']);
+    assert.strictEqual(proofs[0].length, 2);
+});
+
+test('single record root is the leaf', () => {
+    const { proofs } = merkleTree(records.slice(0, 1));
+    const leaf = leafHash(...records[0]);
+
+    assert.strictEqual(leaf.length, 32);
+    assert.deepStrictEqual(proofs, [[]]);
+});
@@ -0,0 +1,32 @@
+import fs from 'fs';
+import { program } from 'commander';
+import { merkleTree } from './merkle.js';
+
+program
+    .description('Compute the Merkle root of the snapshot and the proofs for `register_*` methods of the snapshot contract.')
+    .option('--json <type>', 'Path to the json snapshot', process.env.JSON_PATH);
+
+program.parse(process.argv);
+const options = program.opts();
+
+const snapshotJson = JSON.parse(fs.readFileSync(options.json, 'utf-8'));
+const records = snapshotJson.data.map((record) => [record.account_id, {
+    active_months: record.active_months,
+    stake: record.stake
+}]);
+
+const { root, proofs } = merkleTree(records);
+
+// Each proof is the `SnapshotProof` argument of the registration
+const output = {
+    block_id: snapshotJson.block_id,
+    root,
+    proofs: Object.fromEntries(records.map(([accountId, userData], i) => [accountId, {
+        user_data: userData,
+        path: proofs[i],
+    }])),
+};
+
+console.log(`Snapshot root: ${root}`);
+console.log(`Writing proofs to proofs-${snapshotJson.block_id}.json`);
+fs.writeFileSync(`proofs-${snapshotJson.block_id}.json`, JSON.stringify(output));